@@ -0,0 +1,21 @@
+use thiserror::Error;
+
+use crate::handle::MatchClosed;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum MatchError {
+    #[error("There is no match in this room")]
+    NotFound,
+    #[error("The match has not started yet")]
+    NotStarted,
+    #[error(transparent)]
+    Ended(#[from] MatchClosed),
+    #[error("The user is not playing in the match")]
+    NotInMatch,
+    #[error("It is not the user's turn")]
+    NotYourTurn,
+    #[error("Another player jumped in with the same card first")]
+    JumpInLost,
+}
+
+pub type Result<T, E = MatchError> = std::result::Result<T, E>;
@@ -0,0 +1,658 @@
+use std::{collections::VecDeque, time::Duration};
+
+use runo::{
+    card::{Card, DeckCard, PlayedCard},
+    error::UnoError,
+    rules::Rules,
+    turn::{PlayAction, TurnAction, TurnActionResult},
+    uno::{PendingWildDraw, PlayTurnResult, StartingCardEffect, StartingPlayerChoice, Uno},
+};
+use thiserror::Error;
+use tokio::{
+    sync::{broadcast, mpsc, oneshot},
+    time::Instant,
+};
+
+const COMMAND_BUFFER_SIZE: usize = 32;
+const EVENT_BUFFER_SIZE: usize = 64;
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+const MAX_LOG_ENTRIES: usize = 50;
+/// How long to wait for other players jumping in with the same card before picking one of them.
+const JUMP_IN_WINDOW: Duration = Duration::from_millis(200);
+
+#[derive(Clone, Debug)]
+pub struct MatchOptions {
+    /// How long a player may take for their turn before it is skipped. `None` disables it.
+    pub turn_timeout: Option<Duration>,
+    /// The rules every match is started with.
+    pub rules: Rules,
+}
+
+impl Default for MatchOptions {
+    fn default() -> Self {
+        Self {
+            turn_timeout: Some(Duration::from_secs(120)),
+            rules: Rules::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MatchCommand {
+    Turn {
+        player_id: u64,
+        action: TurnAction,
+    },
+    Join {
+        player_id: u64,
+        player_name: String,
+    },
+    Leave {
+        player_id: u64,
+    },
+    /// Checks whether the current turn has timed out. The match also does this by itself every
+    /// second, so this is mostly useful for driving the timer manually.
+    TimeoutTick,
+    Snapshot,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MatchEvent {
+    /// Who went first and how they were picked, which is the first event of every match.
+    StartingPlayer(StartingPlayerChoice),
+    /// The card the match started with and what it did, which always follows the starting
+    /// player.
+    StartingCard {
+        card: PlayedCard,
+        effect: StartingCardEffect,
+    },
+    TurnPlayed {
+        player_id: u64,
+        action: TurnAction,
+        /// The card as it ended up on the discard pile, if the action played one.
+        played_card: Option<PlayedCard>,
+        result: PlayTurnResult,
+    },
+    NotYourTurn {
+        player_id: u64,
+    },
+    /// Another player closer in turn order jumped in with the same card at the same time, so
+    /// the player's card was not played.
+    JumpInLost {
+        player_id: u64,
+    },
+    NotInMatch {
+        player_id: u64,
+    },
+    PlayerJoined {
+        player_id: u64,
+    },
+    JoinFailed {
+        player_id: u64,
+        error: UnoError,
+    },
+    PlayerLeft {
+        player_id: u64,
+    },
+    TurnTimedOut {
+        player_id: u64,
+    },
+    /// Nothing happened, e.g. a timeout tick before the turn timed out.
+    Idle,
+    Snapshot(Box<MatchSnapshot>),
+    /// A team won or fewer than two players are left, the match does not accept any more
+    /// commands.
+    MatchEnded {
+        winner_ids: Vec<u64>,
+        /// The team that won a team match and the points it scored.
+        winning_team: Option<(usize, u32)>,
+    },
+}
+
+/// The state of a match at one point in time, including every player's hand. Frontends decide
+/// what of it to show to whom.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MatchSnapshot {
+    pub current_turn_player_id: u64,
+    pub next_turn_player_id: u64,
+    pub last_played_card: PlayedCard,
+    pub hands: Vec<(u64, Vec<DeckCard>)>,
+    pub names: Vec<(u64, String)>,
+    pub winner_ids: Vec<u64>,
+    pub pending_wild_draw: Option<PendingWildDraw>,
+    /// The player that played a 7 and has to choose whom to swap hands with.
+    pub pending_hand_swap: Option<u64>,
+    /// Whether the match started with a wild card whose color the current player has yet to
+    /// choose.
+    pub starting_color_pending: bool,
+    /// The player ids of every team, empty unless it is a team match.
+    pub teams: Vec<Vec<u64>>,
+    pub winning_team: Option<usize>,
+    pub rules: Rules,
+    /// The most recent events that changed the match, oldest first.
+    pub log: Vec<MatchEvent>,
+}
+
+impl MatchSnapshot {
+    fn new(game: &Uno, log: &VecDeque<MatchEvent>) -> Self {
+        Self {
+            current_turn_player_id: game.get_current_turn_player_id(),
+            next_turn_player_id: game.get_next_turn_player_id(),
+            last_played_card: game.get_last_played_card().clone(),
+            hands: game
+                .get_player_ids()
+                .into_iter()
+                .filter_map(|id| game.get_player(&id).map(|x| (id, x.hand.clone())))
+                .collect(),
+            names: game
+                .get_player_ids()
+                .into_iter()
+                .filter_map(|id| game.get_player(&id).map(|x| (id, x.name().to_string())))
+                .collect(),
+            winner_ids: game.get_winner_ids(),
+            pending_wild_draw: game.get_pending_wild_draw().cloned(),
+            pending_hand_swap: game.get_pending_hand_swap(),
+            starting_color_pending: game.is_starting_color_pending(),
+            teams: game.get_teams().to_vec(),
+            winning_team: game.get_winning_team(),
+            rules: game.get_rules().clone(),
+            log: log.iter().cloned().collect(),
+        }
+    }
+
+    pub fn hand(&self, player_id: u64) -> Option<&[DeckCard]> {
+        self.hands
+            .iter()
+            .find(|(id, _)| *id == player_id)
+            .map(|(_, hand)| hand.as_slice())
+    }
+}
+
+#[derive(Error, Clone, Copy, Debug, PartialEq, Eq)]
+#[error("The match is no longer running")]
+pub struct MatchClosed;
+
+#[derive(Debug)]
+struct Envelope {
+    command: MatchCommand,
+    reply: oneshot::Sender<MatchEvent>,
+}
+
+/// A handle to a match running in its own task. The task owns the `Uno` game and applies
+/// commands one at a time, which makes it the single place where the game state changes.
+#[derive(Clone, Debug)]
+pub struct MatchHandle {
+    commands: mpsc::Sender<Envelope>,
+    events: broadcast::Sender<MatchEvent>,
+}
+
+impl MatchHandle {
+    pub fn spawn(game: Uno, options: MatchOptions) -> Self {
+        let (commands, receiver) = mpsc::channel(COMMAND_BUFFER_SIZE);
+        let (events, _) = broadcast::channel(EVENT_BUFFER_SIZE);
+        // Nobody can be subscribed yet, so how the match started only goes into the log
+        let starting_player = MatchEvent::StartingPlayer(game.get_starting_player().clone());
+        let starting_card = MatchEvent::StartingCard {
+            card: game.get_last_played_card().clone(),
+            effect: game.get_starting_card_effect().clone(),
+        };
+
+        let runner = MatchRunner {
+            game,
+            options,
+            turn_player_id: None,
+            turn_started_at: Instant::now(),
+            log: VecDeque::from([starting_player, starting_card]),
+            events: events.clone(),
+        };
+        tokio::spawn(runner.run(receiver));
+
+        Self { commands, events }
+    }
+
+    /// Sends a command to the match and waits for the event it resulted in.
+    pub async fn send(&self, command: MatchCommand) -> Result<MatchEvent, MatchClosed> {
+        let (reply, response) = oneshot::channel();
+        self.commands
+            .send(Envelope { command, reply })
+            .await
+            .map_err(|_| MatchClosed)?;
+        response.await.map_err(|_| MatchClosed)
+    }
+
+    pub async fn snapshot(&self) -> Result<MatchSnapshot, MatchClosed> {
+        match self.send(MatchCommand::Snapshot).await? {
+            MatchEvent::Snapshot(snapshot) => Ok(*snapshot),
+            _ => unreachable!("A snapshot command always results in a snapshot."),
+        }
+    }
+
+    /// Whether the match has ended and no longer accepts commands.
+    pub fn is_closed(&self) -> bool {
+        self.commands.is_closed()
+    }
+
+    /// Receives every event of the match, including the ones that were not caused by a command
+    /// such as turn timeouts.
+    pub fn subscribe(&self) -> broadcast::Receiver<MatchEvent> {
+        self.events.subscribe()
+    }
+}
+
+struct MatchRunner {
+    game: Uno,
+    options: MatchOptions,
+    turn_player_id: Option<u64>,
+    turn_started_at: Instant,
+    log: VecDeque<MatchEvent>,
+    events: broadcast::Sender<MatchEvent>,
+}
+
+impl MatchRunner {
+    async fn run(mut self, mut receiver: mpsc::Receiver<Envelope>) {
+        let mut interval = tokio::time::interval(TICK_INTERVAL);
+        self.restart_turn_timer();
+
+        loop {
+            let envelopes = tokio::select! {
+                envelope = receiver.recv() => {
+                    let Some(envelope) = envelope else {
+                        break;
+                    };
+                    self.collect_batch(envelope, &mut receiver).await
+                }
+                _ = interval.tick() => {
+                    let event = self.handle(MatchCommand::TimeoutTick);
+                    if self.publish(event) {
+                        break;
+                    }
+                    continue;
+                }
+            };
+
+            // Jump-ins of the same batch compete for the card that was on top when it started
+            let contested_card = self.game.get_last_played_card().clone();
+            let mut jump_in_winner_id = None;
+            for Envelope { command, reply } in envelopes {
+                let contender_id = self.jump_in_contender(&command, &contested_card);
+                let event = match (contender_id, jump_in_winner_id) {
+                    (Some(player_id), Some(winner_id)) if player_id != winner_id => {
+                        MatchEvent::JumpInLost { player_id }
+                    }
+                    _ => self.handle(command),
+                };
+                if let (
+                    Some(player_id),
+                    MatchEvent::TurnPlayed {
+                        played_card: Some(_),
+                        ..
+                    },
+                ) = (contender_id, &event)
+                {
+                    jump_in_winner_id.get_or_insert(player_id);
+                }
+                // The sender not waiting for the reply anymore is not a problem
+                let _ = reply.send(event.clone());
+                if self.publish(event) {
+                    return;
+                }
+            }
+        }
+    }
+
+    /// Collects the commands that arrived together with `first`, in the order they should be
+    /// applied in. With [`Rules::jump_in`], cards played at the same time, e.g. by two players
+    /// jumping in with the same card, go to whoever is closest in turn order, and the current
+    /// player always goes first. Only the first of them gets to play a duplicate of the top card,
+    /// see [`MatchEvent::JumpInLost`]. Everything else is applied in the order it arrived in.
+    async fn collect_batch(
+        &self,
+        first: Envelope,
+        receiver: &mut mpsc::Receiver<Envelope>,
+    ) -> Vec<Envelope> {
+        let wait_for_jump_ins = self.game.get_rules().jump_in
+            && matches!(
+                first.command,
+                MatchCommand::Turn { player_id, action: TurnAction::Play(_) }
+                    if player_id != self.game.get_current_turn_player_id()
+            );
+        let mut envelopes = vec![first];
+
+        if wait_for_jump_ins {
+            let deadline = Instant::now() + JUMP_IN_WINDOW;
+            while let Ok(Some(envelope)) = tokio::time::timeout_at(deadline, receiver.recv()).await
+            {
+                envelopes.push(envelope);
+            }
+        } else {
+            while let Ok(envelope) = receiver.try_recv() {
+                envelopes.push(envelope);
+            }
+        }
+
+        if self.game.get_rules().jump_in {
+            envelopes = self.arbitrate_plays(envelopes);
+        }
+        envelopes
+    }
+
+    /// Sorts the cards played in `envelopes` by turn distance, leaving every other command where
+    /// it is.
+    fn arbitrate_plays(&self, envelopes: Vec<Envelope>) -> Vec<Envelope> {
+        let mut slots = Vec::with_capacity(envelopes.len());
+        let mut plays = Vec::new();
+        for envelope in envelopes {
+            match &envelope.command {
+                MatchCommand::Turn {
+                    player_id,
+                    action: TurnAction::Play(_),
+                } => {
+                    let distance = self.game.get_turn_distance(player_id);
+                    plays.push((distance.unwrap_or(usize::MAX), envelope));
+                    slots.push(None);
+                }
+                _ => slots.push(Some(envelope)),
+            }
+        }
+
+        // The sort is stable, so plays of the same player keep their order
+        plays.sort_by_key(|(distance, _)| *distance);
+        let mut plays = plays.into_iter().map(|(_, envelope)| envelope);
+        slots
+            .into_iter()
+            .map(|slot| slot.unwrap_or_else(|| plays.next().expect("Every play has a slot.")))
+            .collect()
+    }
+
+    /// Returns the player if the command plays an exact duplicate of `contested_card`, which every
+    /// player that would jump in with it competes for.
+    fn jump_in_contender(
+        &self,
+        command: &MatchCommand,
+        contested_card: &PlayedCard,
+    ) -> Option<u64> {
+        let MatchCommand::Turn {
+            player_id,
+            action: TurnAction::Play(PlayAction::Card { card_id, .. }),
+        } = command
+        else {
+            return None;
+        };
+        if !self.game.get_rules().jump_in {
+            return None;
+        }
+
+        let deck_card = self.game.get_player(player_id)?.get_card(*card_id)?;
+        match (&deck_card.card, contested_card) {
+            (Card::Colored(color, card), PlayedCard::Colored(top_color, top_card))
+                if color == top_color && card == top_card =>
+            {
+                Some(*player_id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Logs and broadcasts the event, returns whether the match has ended.
+    fn publish(&mut self, event: MatchEvent) -> bool {
+        if Self::is_logged(&event) {
+            if self.log.len() == MAX_LOG_ENTRIES {
+                self.log.pop_front();
+            }
+            self.log.push_back(event.clone());
+        }
+
+        if !matches!(event, MatchEvent::Idle | MatchEvent::Snapshot(_)) {
+            // Having no subscribers is not a problem either
+            let _ = self.events.send(event);
+        }
+
+        if self.game.is_over() {
+            let winning_team = self
+                .game
+                .get_winning_team()
+                .map(|team| (team, self.game.get_team_score(team)));
+            let _ = self.events.send(MatchEvent::MatchEnded {
+                winner_ids: self.game.get_winner_ids(),
+                winning_team,
+            });
+            return true;
+        }
+
+        if self.turn_player_id != Some(self.game.get_current_turn_player_id()) {
+            self.restart_turn_timer();
+        }
+        false
+    }
+
+    fn handle(&mut self, command: MatchCommand) -> MatchEvent {
+        match command {
+            MatchCommand::Turn { player_id, action } => self.play_turn(player_id, action),
+            MatchCommand::Join {
+                player_id,
+                player_name,
+            } => match self.game.add_player(player_id, player_name) {
+                Ok(_) => MatchEvent::PlayerJoined { player_id },
+                Err(error) => MatchEvent::JoinFailed { player_id, error },
+            },
+            MatchCommand::Leave { player_id } => match self.game.remove_player(&player_id) {
+                Ok(_) => MatchEvent::PlayerLeft { player_id },
+                Err(_) => MatchEvent::NotInMatch { player_id },
+            },
+            MatchCommand::TimeoutTick => self.check_turn_timeout(),
+            MatchCommand::Snapshot => {
+                MatchEvent::Snapshot(Box::new(MatchSnapshot::new(&self.game, &self.log)))
+            }
+        }
+    }
+
+    fn play_turn(&mut self, player_id: u64, action: TurnAction) -> MatchEvent {
+        if self.game.get_player(&player_id).is_none() {
+            return MatchEvent::NotInMatch { player_id };
+        }
+
+        let result = self.game.play_turn(player_id, action.clone());
+        if result.turn_action_result == TurnActionResult::NotYourTurn {
+            return MatchEvent::NotYourTurn { player_id };
+        }
+        let played_card = match (&action, &result.turn_action_result) {
+            (TurnAction::Play(_), turn_action_result) if !turn_action_result.is_rejected() => {
+                Some(self.game.get_last_played_card().clone())
+            }
+            (TurnAction::Draw, TurnActionResult::DrewAndPlayed(..)) => {
+                Some(self.game.get_last_played_card().clone())
+            }
+            _ => None,
+        };
+
+        MatchEvent::TurnPlayed {
+            player_id,
+            action,
+            played_card,
+            result,
+        }
+    }
+
+    fn check_turn_timeout(&mut self) -> MatchEvent {
+        let Some(turn_timeout) = self.options.turn_timeout else {
+            return MatchEvent::Idle;
+        };
+        if self.turn_started_at.elapsed() < turn_timeout {
+            return MatchEvent::Idle;
+        }
+
+        let player_id = self.game.get_current_turn_player_id();
+        self.game.skip_turn();
+        self.restart_turn_timer();
+
+        MatchEvent::TurnTimedOut { player_id }
+    }
+
+    /// Whether the event changed the match and should show up in its log.
+    fn is_logged(event: &MatchEvent) -> bool {
+        match event {
+            MatchEvent::TurnPlayed { result, .. } => !result.turn_action_result.is_rejected(),
+            MatchEvent::StartingPlayer(_)
+            | MatchEvent::StartingCard { .. }
+            | MatchEvent::PlayerJoined { .. }
+            | MatchEvent::PlayerLeft { .. }
+            | MatchEvent::TurnTimedOut { .. } => true,
+            _ => false,
+        }
+    }
+
+    fn restart_turn_timer(&mut self) {
+        self.turn_player_id = Some(self.game.get_current_turn_player_id());
+        self.turn_started_at = Instant::now();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use runo::rules::StartingPlayer;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn simultaneous_jump_ins_go_to_closest_player() {
+        let players_info = (0..4).map(|id| (id, format!("Player {id}"))).collect();
+        let rules = Rules {
+            jump_in: true,
+            starting_player: StartingPlayer::Host,
+            ..Default::default()
+        };
+        let mut game = Uno::new_with_seats(players_info, 0, rules, Some(0), None).unwrap();
+        let PlayedCard::Colored(color, card) = game.get_last_played_card().clone() else {
+            unreachable!("Matches with seed 0 start with a number card.");
+        };
+
+        let mut card_ids = BTreeMap::new();
+        for player_id in [2, 3] {
+            let player = game.get_player_mut(&player_id).unwrap();
+            player.hand[0].card = Card::Colored(color, card.clone());
+            card_ids.insert(player_id, player.hand[0].id);
+        }
+        let handle = MatchHandle::spawn(
+            game,
+            MatchOptions {
+                turn_timeout: None,
+                ..Default::default()
+            },
+        );
+        let mut events = handle.subscribe();
+
+        let jump_in = |player_id| MatchCommand::Turn {
+            player_id,
+            action: TurnAction::Play(PlayAction::Card {
+                card_id: card_ids[&player_id],
+                chosen_color: None,
+                uno: false,
+            }),
+        };
+        // Player 3 is further away from the current player, so they lose despite being first
+        let (first, second) = tokio::join!(handle.send(jump_in(3)), handle.send(jump_in(2)));
+        assert_eq!(first, Ok(MatchEvent::JumpInLost { player_id: 3 }));
+        assert!(matches!(second, Ok(MatchEvent::TurnPlayed { .. })));
+
+        assert!(matches!(
+            events.recv().await,
+            Ok(MatchEvent::TurnPlayed { player_id: 2, .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn batched_commands_other_than_plays_keep_their_order() {
+        let players_info = (0..4).map(|id| (id, format!("Player {id}"))).collect();
+        let rules = Rules {
+            jump_in: true,
+            starting_player: StartingPlayer::Host,
+            ..Default::default()
+        };
+        let game = Uno::new_with_seats(players_info, 0, rules, Some(0), None).unwrap();
+        let runner = MatchRunner {
+            game,
+            options: MatchOptions::default(),
+            turn_player_id: None,
+            turn_started_at: Instant::now(),
+            log: VecDeque::new(),
+            events: broadcast::channel(EVENT_BUFFER_SIZE).0,
+        };
+        let envelope = |player_id, action| Envelope {
+            command: MatchCommand::Turn { player_id, action },
+            reply: oneshot::channel().0,
+        };
+
+        // Player 1 is closer in turn order, but calls player 3 out only after they said UNO
+        let (sender, mut receiver) = mpsc::channel(COMMAND_BUFFER_SIZE);
+        sender.send(envelope(1, TurnAction::Callout)).await.unwrap();
+        let envelopes = runner
+            .collect_batch(envelope(3, TurnAction::Uno), &mut receiver)
+            .await;
+
+        let commands = envelopes.into_iter().map(|x| x.command).collect::<Vec<_>>();
+        assert_eq!(
+            commands,
+            vec![
+                MatchCommand::Turn {
+                    player_id: 3,
+                    action: TurnAction::Uno
+                },
+                MatchCommand::Turn {
+                    player_id: 1,
+                    action: TurnAction::Callout
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn team_match_ends_when_a_team_wins() {
+        let players = (0..4)
+            .map(|id| (id, format!("Player {id}")))
+            .collect::<Vec<_>>();
+        let teams = vec![players[..2].to_vec(), players[2..].to_vec()];
+        let rules = Rules {
+            starting_player: StartingPlayer::Host,
+            ..Default::default()
+        };
+        let mut game = Uno::new_with_teams(teams, 0, rules, Some(0), None).unwrap();
+        let PlayedCard::Colored(color, card) = game.get_last_played_card().clone() else {
+            unreachable!("Matches with seed 0 start with a number card.");
+        };
+        let player = game.get_player_mut(&0).unwrap();
+        player.hand.truncate(1);
+        player.hand[0].card = Card::Colored(color, card);
+        let card_id = player.hand[0].id;
+        let score = [2, 3]
+            .iter()
+            .flat_map(|x| &game.get_player(x).unwrap().hand)
+            .map(|x| x.card.points())
+            .sum();
+
+        let handle = MatchHandle::spawn(game, MatchOptions::default());
+        let mut events = handle.subscribe();
+        let command = MatchCommand::Turn {
+            player_id: 0,
+            action: TurnAction::Play(PlayAction::Card {
+                card_id,
+                chosen_color: None,
+                uno: false,
+            }),
+        };
+        handle.send(command).await.unwrap();
+
+        assert!(matches!(
+            events.recv().await,
+            Ok(MatchEvent::TurnPlayed { player_id: 0, .. })
+        ));
+        assert_eq!(
+            events.recv().await,
+            Ok(MatchEvent::MatchEnded {
+                winner_ids: vec![0],
+                winning_team: Some((0, score)),
+            })
+        );
+    }
+}
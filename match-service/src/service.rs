@@ -0,0 +1,944 @@
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    hash::Hash,
+    sync::Arc,
+};
+
+use rand::seq::SliceRandom;
+use runo::{
+    error::UnoError,
+    turn::TurnAction,
+    uno::{PlayTurnResult, Uno},
+};
+use tokio::sync::{broadcast, Mutex};
+
+use crate::{
+    error::{MatchError, Result},
+    handle::{MatchCommand, MatchEvent, MatchHandle, MatchOptions, MatchSnapshot},
+};
+
+/// Identifies a room that holds at most one match at a time, like a channel of a chat platform.
+pub trait RoomKey: Clone + Eq + Hash + Send + Sync + 'static {}
+
+impl<T> RoomKey for T where T: Clone + Eq + Hash + Send + Sync + 'static {}
+
+/// Identifies a user of the chat platform. Matches identify their players by `u64`, so user ids
+/// have to convert to and from it.
+pub trait UserKey: Copy + Ord + Hash + From<u64> + Into<u64> + Send + Sync + 'static {}
+
+impl<T> UserKey for T where T: Copy + Ord + Hash + From<u64> + Into<u64> + Send + Sync + 'static {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CreateOutcome<U> {
+    AlreadyExists(BTreeSet<U>),
+    AlreadyJoined(BTreeSet<U>),
+    AlreadyStarted(BTreeSet<U>),
+    Created,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum JoinOutcome<U> {
+    AlreadyJoined(BTreeSet<U>),
+    DoesNotExist,
+    Joined(BTreeSet<U>),
+    JoinedOngoing(BTreeSet<U>),
+    Failed(UnoError),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LeaveOutcome<U> {
+    DoesNotExist,
+    NotInMatch,
+    Left,
+    /// The host left a pending match, which is handed to the next queued user if there is one.
+    /// Otherwise the match is removed.
+    LeftAsHost(Option<U>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartOutcome<U> {
+    AlreadyStarted(BTreeSet<U>),
+    DoesNotExist,
+    NotHost,
+    Started,
+    Failed(UnoError),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TeamOutcome<U> {
+    AlreadyStarted,
+    DoesNotExist,
+    NotInMatch,
+    /// The users that picked a team so far by team, in the order they joined.
+    Picked(BTreeMap<usize, Vec<U>>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpenHandsOutcome {
+    DoesNotExist,
+    NotHost,
+    /// Whether spectators can see every player's hand now.
+    Toggled(bool),
+}
+
+/// What spectators of a match get to see. Frontends should only show the hands if `open_hands` is
+/// set.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpectatorView {
+    pub snapshot: MatchSnapshot,
+    pub spectators_count: usize,
+    pub open_hands: bool,
+}
+
+enum Room<U> {
+    Pending {
+        host: U,
+        /// The users that joined so far, in the order they joined.
+        queued_users: Vec<(U, String)>,
+        /// The team every user that picked one is in. As soon as anyone picks a team, the match
+        /// is started as a team match.
+        teams: HashMap<U, usize>,
+        open_hands: bool,
+    },
+    Ongoing {
+        host: U,
+        handle: MatchHandle,
+        spectators: HashSet<U>,
+        open_hands: bool,
+    },
+}
+
+impl<U: UserKey> Room<U> {
+    async fn player_ids(&self) -> BTreeSet<U> {
+        match self {
+            Room::Pending { queued_users, .. } => queued_users.iter().map(|(id, _)| *id).collect(),
+            Room::Ongoing { handle, .. } => match handle.snapshot().await {
+                Ok(snapshot) => snapshot.hands.iter().map(|(id, _)| U::from(*id)).collect(),
+                Err(_) => BTreeSet::new(),
+            },
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        matches!(self, Room::Ongoing { handle, .. } if handle.is_closed())
+    }
+}
+
+/// Owns the matches of every room, from the lobby until the match ends. It knows nothing about the
+/// platform it is used on, so frontends turn its outcomes into messages themselves.
+pub struct MatchService<R, U> {
+    rooms: Mutex<HashMap<R, Arc<Mutex<Room<U>>>>>,
+    /// Who dealt the last match of every room, so that the next one is dealt by the player after
+    /// them, see [`StartingPlayer::LeftOfDealer`](runo::rules::StartingPlayer::LeftOfDealer).
+    dealers: Mutex<HashMap<R, U>>,
+    options: MatchOptions,
+}
+
+impl<R: RoomKey, U: UserKey> MatchService<R, U> {
+    pub fn new(options: MatchOptions) -> Self {
+        Self {
+            rooms: Mutex::new(HashMap::new()),
+            dealers: Mutex::new(HashMap::new()),
+            options,
+        }
+    }
+
+    /// Returns the room with the given id. The rooms are only locked for the lookup, so working
+    /// with one match never blocks the others.
+    async fn get_room(&self, room_id: &R) -> Option<Arc<Mutex<Room<U>>>> {
+        self.rooms.lock().await.get(room_id).cloned()
+    }
+
+    /// Returns the handle of the ongoing match in the room. The room is only locked while cloning
+    /// the handle.
+    async fn get_handle(&self, room_id: &R) -> Result<MatchHandle> {
+        let room = self.get_room(room_id).await.ok_or(MatchError::NotFound)?;
+        let room = room.lock().await;
+        match &*room {
+            Room::Pending { .. } => Err(MatchError::NotStarted),
+            Room::Ongoing { handle, .. } => Ok(handle.clone()),
+        }
+    }
+
+    pub async fn create(&self, room_id: R, user_id: U, user_name: String) -> CreateOutcome<U> {
+        loop {
+            let existing_room = self.get_room(&room_id).await;
+            if let Some(room) = &existing_room {
+                let room = room.lock().await;
+                // A match that has ended makes room for a new one
+                if !room.is_closed() {
+                    let player_ids = room.player_ids().await;
+                    return match *room {
+                        Room::Pending { .. } if player_ids.contains(&user_id) => {
+                            CreateOutcome::AlreadyJoined(player_ids)
+                        }
+                        Room::Pending { .. } => CreateOutcome::AlreadyExists(player_ids),
+                        Room::Ongoing { .. } => CreateOutcome::AlreadyStarted(player_ids),
+                    };
+                }
+            }
+
+            let mut rooms = self.rooms.lock().await;
+            // Someone else may have created a match in the meantime, which is checked again
+            let unchanged = match (rooms.get(&room_id), &existing_room) {
+                (Some(room), Some(existing_room)) => Arc::ptr_eq(room, existing_room),
+                (None, None) => true,
+                _ => false,
+            };
+            if unchanged {
+                rooms.insert(
+                    room_id,
+                    Arc::new(Mutex::new(Room::Pending {
+                        host: user_id,
+                        queued_users: vec![(user_id, user_name)],
+                        teams: HashMap::new(),
+                        open_hands: false,
+                    })),
+                );
+                return CreateOutcome::Created;
+            }
+        }
+    }
+
+    /// Queues the user for a pending match, or deals them in to an ongoing one.
+    pub async fn join(&self, room_id: &R, user_id: U, user_name: String) -> JoinOutcome<U> {
+        let Some(room) = self.get_room(room_id).await else {
+            return JoinOutcome::DoesNotExist;
+        };
+
+        let mut room = room.lock().await;
+        match &mut *room {
+            Room::Pending { queued_users, .. } => {
+                if queued_users.iter().any(|(id, _)| *id == user_id) {
+                    JoinOutcome::AlreadyJoined(room.player_ids().await)
+                } else {
+                    queued_users.push((user_id, user_name));
+                    JoinOutcome::Joined(room.player_ids().await)
+                }
+            }
+            Room::Ongoing { handle, .. } => {
+                let command = MatchCommand::Join {
+                    player_id: user_id.into(),
+                    player_name: user_name,
+                };
+                match handle.send(command).await {
+                    Ok(MatchEvent::PlayerJoined { .. }) => {
+                        JoinOutcome::JoinedOngoing(room.player_ids().await)
+                    }
+                    Ok(MatchEvent::JoinFailed {
+                        error: UnoError::PlayerAlreadyExists,
+                        ..
+                    }) => JoinOutcome::AlreadyJoined(room.player_ids().await),
+                    Ok(MatchEvent::JoinFailed { error, .. }) => JoinOutcome::Failed(error),
+                    Ok(_) => unreachable!("A join command only results in join events."),
+                    Err(_) => JoinOutcome::DoesNotExist,
+                }
+            }
+        }
+    }
+
+    pub async fn leave(&self, room_id: &R, user_id: U) -> LeaveOutcome<U> {
+        let Some(room) = self.get_room(room_id).await else {
+            return LeaveOutcome::DoesNotExist;
+        };
+
+        let mut room = room.lock().await;
+        match &mut *room {
+            Room::Pending {
+                host,
+                queued_users,
+                teams,
+                ..
+            } => {
+                let Some(index) = queued_users.iter().position(|(id, _)| *id == user_id) else {
+                    return LeaveOutcome::NotInMatch;
+                };
+                queued_users.remove(index);
+                teams.remove(&user_id);
+                if *host != user_id {
+                    return LeaveOutcome::Left;
+                }
+
+                match queued_users.first().map(|(id, _)| id) {
+                    Some(new_host) => {
+                        *host = *new_host;
+                        LeaveOutcome::LeftAsHost(Some(*new_host))
+                    }
+                    None => {
+                        // Only ended matches are replaced, so the room is still the same one
+                        self.rooms.lock().await.remove(room_id);
+                        LeaveOutcome::LeftAsHost(None)
+                    }
+                }
+            }
+            Room::Ongoing { handle, .. } => {
+                let command = MatchCommand::Leave {
+                    player_id: user_id.into(),
+                };
+                match handle.send(command).await {
+                    Ok(MatchEvent::PlayerLeft { .. }) => LeaveOutcome::Left,
+                    Ok(_) => LeaveOutcome::NotInMatch,
+                    Err(_) => LeaveOutcome::DoesNotExist,
+                }
+            }
+        }
+    }
+
+    /// Puts the user in a team of the pending match, which makes it a team match, see
+    /// [`Uno::new_with_teams`].
+    pub async fn pick_team(&self, room_id: &R, user_id: U, team: usize) -> TeamOutcome<U> {
+        let Some(room) = self.get_room(room_id).await else {
+            return TeamOutcome::DoesNotExist;
+        };
+
+        let mut room = room.lock().await;
+        let Room::Pending {
+            queued_users,
+            teams,
+            ..
+        } = &mut *room
+        else {
+            return TeamOutcome::AlreadyStarted;
+        };
+        if !queued_users.iter().any(|(id, _)| *id == user_id) {
+            return TeamOutcome::NotInMatch;
+        }
+
+        teams.insert(user_id, team);
+        let picked = Self::group_teams(queued_users, teams)
+            .into_iter()
+            .map(|(team, users)| (team, users.into_iter().map(|(id, _)| id).collect()))
+            .collect();
+        TeamOutcome::Picked(picked)
+    }
+
+    /// Groups the users that picked a team by team, keeping their order.
+    fn group_teams(
+        users: &[(U, String)],
+        teams: &HashMap<U, usize>,
+    ) -> BTreeMap<usize, Vec<(U, String)>> {
+        let mut grouped = BTreeMap::<_, Vec<_>>::new();
+        for (user_id, user_name) in users {
+            if let Some(team) = teams.get(user_id) {
+                grouped
+                    .entry(*team)
+                    .or_default()
+                    .push((*user_id, user_name.clone()));
+            }
+        }
+        grouped
+    }
+
+    /// Deals the cards to the queued users and starts running the match. Only the host can start
+    /// it. Players sit in the order they joined in, unless `shuffle_seats` is set. If anyone picked
+    /// a team, everyone has to and the partners sit evenly spread around the table instead.
+    pub async fn start(&self, room_id: &R, user_id: U, shuffle_seats: bool) -> StartOutcome<U> {
+        let Some(room) = self.get_room(room_id).await else {
+            return StartOutcome::DoesNotExist;
+        };
+
+        let mut room = room.lock().await;
+        let Room::Pending {
+            host,
+            queued_users,
+            teams,
+            open_hands,
+        } = &*room
+        else {
+            return StartOutcome::AlreadyStarted(room.player_ids().await);
+        };
+        if *host != user_id {
+            return StartOutcome::NotHost;
+        }
+        if !teams.is_empty() && teams.len() < queued_users.len() {
+            return StartOutcome::Failed(UnoError::InvalidTeams);
+        }
+
+        let mut seats = queued_users.clone();
+        if shuffle_seats {
+            seats.shuffle(&mut rand::thread_rng());
+        }
+        let teams = Self::group_teams(&seats, teams)
+            .into_values()
+            .map(|team| {
+                team.into_iter()
+                    .map(|(id, name)| (id.into(), name))
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+        if !teams.is_empty() {
+            seats = Uno::seat_teams(&teams)
+                .into_iter()
+                .map(|(id, name)| (U::from(id), name))
+                .collect();
+        }
+        let dealer = self.next_dealer(room_id, *host, &seats).await;
+        let game = if teams.is_empty() {
+            let seats = seats
+                .into_iter()
+                .map(|(id, name)| (id.into(), name))
+                .collect();
+            Uno::new_with_seats(
+                seats,
+                rand::random(),
+                self.options.rules.clone(),
+                Some((*host).into()),
+                Some(dealer.into()),
+            )
+        } else {
+            Uno::new_with_teams(
+                teams,
+                rand::random(),
+                self.options.rules.clone(),
+                Some((*host).into()),
+                Some(dealer.into()),
+            )
+        };
+        match game {
+            Ok(game) => {
+                self.dealers.lock().await.insert(room_id.clone(), dealer);
+                *room = Room::Ongoing {
+                    host: *host,
+                    handle: MatchHandle::spawn(game, self.options.clone()),
+                    spectators: HashSet::new(),
+                    open_hands: *open_hands,
+                };
+                StartOutcome::Started
+            }
+            Err(err) => StartOutcome::Failed(err),
+        }
+    }
+
+    /// Picks the player seated after the dealer of the room's last match, or the host if that
+    /// dealer is not playing or this is the room's first match. The dealer is only remembered
+    /// once their match started.
+    async fn next_dealer(&self, room_id: &R, host: U, seats: &[(U, String)]) -> U {
+        let last_dealer_index = self
+            .dealers
+            .lock()
+            .await
+            .get(room_id)
+            .and_then(|last_dealer| seats.iter().position(|(id, _)| id == last_dealer));
+        match last_dealer_index {
+            Some(index) => seats[(index + 1) % seats.len()].0,
+            None => host,
+        }
+    }
+
+    /// Toggles whether spectators can see every player's hand. Only the host can toggle it.
+    pub async fn toggle_open_hands(&self, room_id: &R, user_id: U) -> OpenHandsOutcome {
+        let Some(room) = self.get_room(room_id).await else {
+            return OpenHandsOutcome::DoesNotExist;
+        };
+
+        let mut room = room.lock().await;
+        match &mut *room {
+            Room::Pending {
+                host, open_hands, ..
+            }
+            | Room::Ongoing {
+                host, open_hands, ..
+            } => {
+                if *host != user_id {
+                    OpenHandsOutcome::NotHost
+                } else {
+                    *open_hands = !*open_hands;
+                    OpenHandsOutcome::Toggled(*open_hands)
+                }
+            }
+        }
+    }
+
+    /// Returns the current state of the match if the user is one of its players.
+    pub async fn player_snapshot(&self, room_id: &R, user_id: U) -> Result<MatchSnapshot> {
+        let snapshot = self.get_handle(room_id).await?.snapshot().await?;
+        if snapshot.hand(user_id.into()).is_none() {
+            return Err(MatchError::NotInMatch);
+        }
+
+        Ok(snapshot)
+    }
+
+    /// Plays the user's turn. The match checks the turn by itself, so a turn that changed while the
+    /// user was deciding is rejected.
+    pub async fn play_turn(
+        &self,
+        room_id: &R,
+        user_id: U,
+        action: TurnAction,
+    ) -> Result<PlayTurnResult> {
+        let command = MatchCommand::Turn {
+            player_id: user_id.into(),
+            action,
+        };
+        match self.get_handle(room_id).await?.send(command).await? {
+            MatchEvent::TurnPlayed { result, .. } => Ok(result),
+            MatchEvent::NotYourTurn { .. } => Err(MatchError::NotYourTurn),
+            MatchEvent::JumpInLost { .. } => Err(MatchError::JumpInLost),
+            MatchEvent::NotInMatch { .. } => Err(MatchError::NotInMatch),
+            _ => unreachable!("A turn command only results in turn events."),
+        }
+    }
+
+    /// Receives every event of the ongoing match in the room from now on.
+    pub async fn subscribe(&self, room_id: &R) -> Result<broadcast::Receiver<MatchEvent>> {
+        Ok(self.get_handle(room_id).await?.subscribe())
+    }
+
+    /// Registers the user as a spectator and returns the initial view, or `None` if there is no
+    /// ongoing match in the room.
+    pub async fn start_spectating(&self, room_id: &R, user_id: U) -> Option<SpectatorView> {
+        let room = self.get_room(room_id).await?;
+        if let Room::Ongoing { spectators, .. } = &mut *room.lock().await {
+            spectators.insert(user_id);
+        }
+        self.spectator_view(room_id, user_id).await
+    }
+
+    pub async fn stop_spectating(&self, room_id: &R, user_id: U) {
+        let Some(room) = self.get_room(room_id).await else {
+            return;
+        };
+        if let Room::Ongoing { spectators, .. } = &mut *room.lock().await {
+            spectators.remove(&user_id);
+        };
+    }
+
+    /// Returns what the user sees of the match as a spectator, or `None` if there is no ongoing
+    /// match in the room. Players of the match never get to see the other hands.
+    pub async fn spectator_view(&self, room_id: &R, user_id: U) -> Option<SpectatorView> {
+        let room = self.get_room(room_id).await?;
+        let (handle, spectators_count, open_hands) = match &*room.lock().await {
+            Room::Pending { .. } => return None,
+            Room::Ongoing {
+                handle,
+                spectators,
+                open_hands,
+                ..
+            } => (handle.clone(), spectators.len(), *open_hands),
+        };
+
+        let snapshot = handle.snapshot().await.ok()?;
+        let open_hands = open_hands && snapshot.hand(user_id.into()).is_none();
+        Some(SpectatorView {
+            snapshot,
+            spectators_count,
+            open_hands,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use runo::{
+        deck_spec::DeckSpecError,
+        rules::{Rules, StartingPlayer},
+    };
+
+    use super::*;
+
+    const ROOM: u64 = 1;
+
+    fn create_service() -> MatchService<u64, u64> {
+        MatchService::new(MatchOptions {
+            turn_timeout: None,
+            ..Default::default()
+        })
+    }
+
+    async fn create_started_match(service: &MatchService<u64, u64>, user_ids: &[u64]) {
+        service.create(ROOM, user_ids[0], "Host".to_string()).await;
+        for user_id in &user_ids[1..] {
+            service
+                .join(&ROOM, *user_id, format!("User {user_id}"))
+                .await;
+        }
+        assert_eq!(
+            service.start(&ROOM, user_ids[0], false).await,
+            StartOutcome::Started
+        );
+    }
+
+    #[tokio::test]
+    async fn dealer_moves_on_with_every_match() {
+        let service = create_service();
+        let seats = [30, 10, 20].map(|id| (id, String::new()));
+
+        assert_eq!(service.next_dealer(&ROOM, 10, &seats).await, 10);
+        service.dealers.lock().await.insert(ROOM, 10);
+        assert_eq!(service.next_dealer(&ROOM, 10, &seats).await, 20);
+        service.dealers.lock().await.insert(ROOM, 20);
+        assert_eq!(service.next_dealer(&ROOM, 10, &seats).await, 30);
+        // Another room has dealers of its own
+        assert_eq!(service.next_dealer(&(ROOM + 1), 20, &seats).await, 20);
+        // The host deals again if the last dealer is not playing anymore
+        service.dealers.lock().await.insert(ROOM, 30);
+        assert_eq!(service.next_dealer(&ROOM, 10, &seats[1..]).await, 10);
+    }
+
+    #[tokio::test]
+    async fn dealer_stays_if_match_fails_to_start() {
+        let service: MatchService<u64, u64> = MatchService::new(MatchOptions {
+            turn_timeout: None,
+            rules: Rules {
+                deck_count: Some(93),
+                ..Default::default()
+            },
+        });
+        service.create(ROOM, 10, "Host".to_string()).await;
+        service.join(&ROOM, 20, "User 20".to_string()).await;
+
+        assert_eq!(
+            service.start(&ROOM, 10, false).await,
+            StartOutcome::Failed(UnoError::InvalidDeck(DeckSpecError::TooManyCards))
+        );
+        assert_eq!(service.dealers.lock().await.get(&ROOM), None);
+    }
+
+    #[tokio::test]
+    async fn busy_room_does_not_block_other_rooms() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+        service.create(ROOM + 1, 10, "Host".to_string()).await;
+        let room = service.get_room(&ROOM).await.unwrap();
+        let room_guard = room.lock().await;
+
+        // Waits for the busy room without holding on to the others
+        let create = service.create(ROOM, 20, "User 20".to_string());
+        tokio::pin!(create);
+        let waiting = tokio::time::timeout(Duration::from_millis(10), &mut create).await;
+        assert!(waiting.is_err());
+
+        let timeout = Duration::from_secs(1);
+        let other_create = service.create(ROOM + 2, 20, "User 20".to_string());
+        assert_eq!(
+            tokio::time::timeout(timeout, other_create).await,
+            Ok(CreateOutcome::Created)
+        );
+        let other_leave = service.leave(&(ROOM + 1), 10);
+        assert_eq!(
+            tokio::time::timeout(timeout, other_leave).await,
+            Ok(LeaveOutcome::LeftAsHost(None))
+        );
+
+        drop(room_guard);
+        assert_eq!(
+            create.await,
+            CreateOutcome::AlreadyExists(BTreeSet::from([10]))
+        );
+    }
+
+    #[tokio::test]
+    async fn players_sit_in_join_order() {
+        let service = create_service();
+        create_started_match(&service, &[20, 10, 30]).await;
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        let seats = snapshot.hands.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(seats, vec![20, 10, 30]);
+    }
+
+    #[tokio::test]
+    async fn match_starts_with_host_if_configured() {
+        let service = MatchService::new(MatchOptions {
+            turn_timeout: None,
+            rules: Rules {
+                starting_player: StartingPlayer::Host,
+                ..Default::default()
+            },
+        });
+        create_started_match(&service, &[20, 10, 30]).await;
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        assert!(matches!(
+            snapshot.log.first(),
+            Some(MatchEvent::StartingPlayer(choice)) if choice.player_id == 20
+        ));
+    }
+
+    #[tokio::test]
+    async fn partners_sit_opposite_each_other() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+        for user_id in [20, 30, 40] {
+            service.join(&ROOM, user_id, String::new()).await;
+        }
+        for (user_id, team) in [(10, 0), (20, 0), (30, 1)] {
+            service.pick_team(&ROOM, user_id, team).await;
+        }
+        assert_eq!(
+            service.pick_team(&ROOM, 50, 1).await,
+            TeamOutcome::NotInMatch
+        );
+        // Everyone has to be in a team once anyone picked one
+        assert_eq!(
+            service.start(&ROOM, 10, false).await,
+            StartOutcome::Failed(UnoError::InvalidTeams)
+        );
+        assert_eq!(
+            service.pick_team(&ROOM, 40, 1).await,
+            TeamOutcome::Picked(BTreeMap::from([(0, vec![10, 20]), (1, vec![30, 40])]))
+        );
+        assert_eq!(service.start(&ROOM, 10, false).await, StartOutcome::Started);
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        let seats = snapshot.hands.iter().map(|(id, _)| *id).collect::<Vec<_>>();
+        assert_eq!(seats, vec![10, 30, 20, 40]);
+        assert_eq!(snapshot.teams, vec![vec![10, 20], vec![30, 40]]);
+        assert_eq!(
+            service.pick_team(&ROOM, 10, 1).await,
+            TeamOutcome::AlreadyStarted
+        );
+    }
+
+    #[tokio::test]
+    async fn create_reports_existing_match() {
+        let service = create_service();
+
+        assert_eq!(
+            service.create(ROOM, 10, "Host".to_string()).await,
+            CreateOutcome::Created
+        );
+        assert_eq!(
+            service.create(ROOM, 10, "Host".to_string()).await,
+            CreateOutcome::AlreadyJoined(BTreeSet::from([10]))
+        );
+        assert_eq!(
+            service.create(ROOM, 20, "Other".to_string()).await,
+            CreateOutcome::AlreadyExists(BTreeSet::from([10]))
+        );
+    }
+
+    #[tokio::test]
+    async fn join_queues_users_once() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+
+        assert_eq!(
+            service.join(&ROOM, 20, "Other".to_string()).await,
+            JoinOutcome::Joined(BTreeSet::from([10, 20]))
+        );
+        assert_eq!(
+            service.join(&ROOM, 20, "Other".to_string()).await,
+            JoinOutcome::AlreadyJoined(BTreeSet::from([10, 20]))
+        );
+        assert_eq!(
+            service.join(&2, 20, "Other".to_string()).await,
+            JoinOutcome::DoesNotExist
+        );
+    }
+
+    #[tokio::test]
+    async fn only_host_can_start() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+        service.join(&ROOM, 20, "Other".to_string()).await;
+
+        assert_eq!(service.start(&ROOM, 20, false).await, StartOutcome::NotHost);
+        assert_eq!(service.start(&ROOM, 10, false).await, StartOutcome::Started);
+        assert_eq!(
+            service.start(&ROOM, 10, false).await,
+            StartOutcome::AlreadyStarted(BTreeSet::from([10, 20]))
+        );
+    }
+
+    #[tokio::test]
+    async fn start_fails_without_enough_players() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+
+        assert_eq!(
+            service.start(&ROOM, 10, false).await,
+            StartOutcome::Failed(UnoError::NotEnoughPlayers)
+        );
+    }
+
+    #[tokio::test]
+    async fn join_deals_in_to_ongoing_match() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+
+        assert_eq!(
+            service.join(&ROOM, 30, "Late".to_string()).await,
+            JoinOutcome::JoinedOngoing(BTreeSet::from([10, 20, 30]))
+        );
+        assert_eq!(
+            service.join(&ROOM, 30, "Late".to_string()).await,
+            JoinOutcome::AlreadyJoined(BTreeSet::from([10, 20, 30]))
+        );
+
+        let snapshot = service.player_snapshot(&ROOM, 30).await.unwrap();
+        assert_eq!(snapshot.hand(30).unwrap().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn host_leaving_pending_match_hands_it_over() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+        service.join(&ROOM, 20, "Other".to_string()).await;
+
+        assert_eq!(service.leave(&ROOM, 30).await, LeaveOutcome::NotInMatch);
+        assert_eq!(
+            service.leave(&ROOM, 10).await,
+            LeaveOutcome::LeftAsHost(Some(20))
+        );
+        assert_eq!(
+            service.leave(&ROOM, 20).await,
+            LeaveOutcome::LeftAsHost(None)
+        );
+        assert_eq!(service.leave(&ROOM, 20).await, LeaveOutcome::DoesNotExist);
+    }
+
+    #[tokio::test]
+    async fn ended_match_makes_room_for_new_one() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+
+        assert_eq!(service.leave(&ROOM, 20).await, LeaveOutcome::Left);
+        assert_eq!(
+            service.play_turn(&ROOM, 10, TurnAction::Draw).await,
+            Err(MatchError::Ended(crate::handle::MatchClosed))
+        );
+        assert_eq!(
+            service.create(ROOM, 20, "Other".to_string()).await,
+            CreateOutcome::Created
+        );
+    }
+
+    #[tokio::test]
+    async fn play_turn_checks_player_and_turn() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        let current_player_id = snapshot.current_turn_player_id;
+        let other_player_id = snapshot.next_turn_player_id;
+
+        assert_eq!(
+            service.play_turn(&ROOM, 30, TurnAction::Draw).await,
+            Err(MatchError::NotInMatch)
+        );
+        assert_eq!(
+            service
+                .play_turn(&ROOM, other_player_id, TurnAction::Draw)
+                .await,
+            Err(MatchError::NotYourTurn)
+        );
+        // Unlike drawing, a callout can't be rejected, e.g. when the match started with a Wild
+        assert!(service
+            .play_turn(&ROOM, current_player_id, TurnAction::Callout)
+            .await
+            .is_ok());
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        assert!(matches!(
+            snapshot.log.as_slice(),
+            [
+                MatchEvent::StartingPlayer(_),
+                MatchEvent::StartingCard { .. },
+                MatchEvent::TurnPlayed { player_id, .. }
+            ]
+                if *player_id == current_player_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscribers_receive_turns() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+        let mut events = service.subscribe(&ROOM).await.unwrap();
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        let current_player_id = snapshot.current_turn_player_id;
+        service
+            .play_turn(&ROOM, current_player_id, TurnAction::Draw)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            events.recv().await,
+            Ok(MatchEvent::TurnPlayed { player_id, .. }) if player_id == current_player_id
+        ));
+    }
+
+    #[tokio::test]
+    async fn pending_match_has_no_turns() {
+        let service = create_service();
+        service.create(ROOM, 10, "Host".to_string()).await;
+
+        assert_eq!(
+            service.play_turn(&ROOM, 10, TurnAction::Draw).await,
+            Err(MatchError::NotStarted)
+        );
+        assert_eq!(
+            service.play_turn(&2, 10, TurnAction::Draw).await,
+            Err(MatchError::NotFound)
+        );
+        assert_eq!(service.spectator_view(&ROOM, 10).await, None);
+    }
+
+    #[tokio::test]
+    async fn only_host_can_toggle_open_hands() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+
+        assert_eq!(
+            service.toggle_open_hands(&ROOM, 20).await,
+            OpenHandsOutcome::NotHost
+        );
+        assert_eq!(
+            service.toggle_open_hands(&ROOM, 10).await,
+            OpenHandsOutcome::Toggled(true)
+        );
+
+        let view = service.start_spectating(&ROOM, 30).await.unwrap();
+        assert!(view.open_hands);
+        assert_eq!(view.spectators_count, 1);
+
+        service.stop_spectating(&ROOM, 30).await;
+        let view = service.spectator_view(&ROOM, 30).await.unwrap();
+        assert_eq!(view.spectators_count, 0);
+    }
+
+    #[tokio::test]
+    async fn players_do_not_see_open_hands() {
+        let service = create_service();
+        create_started_match(&service, &[10, 20]).await;
+        service.toggle_open_hands(&ROOM, 10).await;
+
+        let view = service.start_spectating(&ROOM, 20).await.unwrap();
+        assert!(!view.open_hands);
+        let view = service.spectator_view(&ROOM, 10).await.unwrap();
+        assert!(!view.open_hands);
+        let view = service.spectator_view(&ROOM, 30).await.unwrap();
+        assert!(view.open_hands);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn turn_is_skipped_after_timeout() {
+        let service = MatchService::new(MatchOptions {
+            turn_timeout: Some(Duration::from_secs(30)),
+            ..Default::default()
+        });
+        create_started_match(&service, &[10, 20]).await;
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        let timed_out_player_id = snapshot.current_turn_player_id;
+
+        tokio::time::sleep(Duration::from_secs(31)).await;
+
+        let snapshot = service.player_snapshot(&ROOM, 10).await.unwrap();
+        assert_eq!(snapshot.next_turn_player_id, timed_out_player_id);
+        assert!(matches!(
+            snapshot.log.as_slice(),
+            [
+                MatchEvent::StartingPlayer(_),
+                MatchEvent::StartingCard { .. },
+                MatchEvent::TurnTimedOut { player_id }
+            ]
+                if *player_id == timed_out_player_id
+        ));
+    }
+}
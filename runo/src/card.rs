@@ -37,6 +37,29 @@ pub enum Card {
     WildDraw,
 }
 
+impl Card {
+    /// What the card is worth to whoever wins while it is still in someone's hand, as the official
+    /// rules score it.
+    pub fn points(&self) -> u32 {
+        match self {
+            Card::Colored(_, ColoredCard::Number(number)) => u32::from(*number),
+            Card::Colored(_, _) => 20,
+            Card::Wild | Card::WildDraw => 50,
+        }
+    }
+
+    /// Whether the card may be played on top of `top_card`, which is the case for wild cards and
+    /// for cards sharing its color or symbol.
+    pub fn can_be_played_on(&self, top_card: &PlayedCard) -> bool {
+        match (self, top_card) {
+            (Card::Wild | Card::WildDraw, _) => true,
+            (Card::Colored(color, _), _) if *color == top_card.color() => true,
+            (Card::Colored(_, card), PlayedCard::Colored(_, top_card)) => card == top_card,
+            (Card::Colored(_, _), _) => false,
+        }
+    }
+}
+
 impl Display for Card {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -56,6 +79,23 @@ impl Display for Card {
     }
 }
 
+/// Identifies a single physical card. Ids are assigned once by `Deck::from_spec` and never change.
+pub type CardId = u32;
+
+/// A card together with the identity of the physical card it is printed on, so that duplicate
+/// cards can be told apart.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeckCard {
+    pub id: CardId,
+    pub card: Card,
+}
+
+impl Display for DeckCard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.card)
+    }
+}
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum PlayedCard {
     Colored(CardColor, ColoredCard),
@@ -76,7 +116,9 @@ impl PlayedCard {
 impl Display for PlayedCard {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            PlayedCard::Colored(color, card) => write!(f, "{} {}", color, card),
+            PlayedCard::Colored(color, card) => {
+                write!(f, "{}", Card::Colored(*color, card.clone()))
+            }
             PlayedCard::Wild(color) => write!(f, "{} Wild", color),
             PlayedCard::WildDraw(color) => write!(f, "{} Wild Draw (+4)", color),
         }
@@ -146,4 +188,36 @@ mod tests {
         let wild_draw = Card::WildDraw;
         assert_eq!(wild_draw.to_string(), "Wild Draw (+4)");
     }
+
+    #[test]
+    fn return_correct_string_for_played_cards() {
+        let red_3 = PlayedCard::Colored(CardColor::Red, ColoredCard::Number(3));
+        assert_eq!(red_3.to_string(), "Red 3");
+
+        let blue_draw = PlayedCard::Colored(CardColor::Blue, ColoredCard::Draw);
+        assert_eq!(blue_draw.to_string(), "Blue Draw (+2)");
+
+        let green_wild_draw = PlayedCard::WildDraw(CardColor::Green);
+        assert_eq!(green_wild_draw.to_string(), "Green Wild Draw (+4)");
+    }
+
+    #[test]
+    fn card_can_be_played_on_same_color_or_symbol() {
+        let top_card = PlayedCard::Colored(CardColor::Red, ColoredCard::Number(5));
+
+        assert!(Card::Colored(CardColor::Red, ColoredCard::Skip).can_be_played_on(&top_card));
+        assert!(Card::Colored(CardColor::Blue, ColoredCard::Number(5)).can_be_played_on(&top_card));
+        assert!(!Card::Colored(CardColor::Blue, ColoredCard::Number(6)).can_be_played_on(&top_card));
+        assert!(!Card::Colored(CardColor::Blue, ColoredCard::Skip).can_be_played_on(&top_card));
+    }
+
+    #[test]
+    fn card_can_be_played_on_chosen_wild_color() {
+        let top_card = PlayedCard::Wild(CardColor::Green);
+
+        assert!(Card::Colored(CardColor::Green, ColoredCard::Draw).can_be_played_on(&top_card));
+        assert!(!Card::Colored(CardColor::Red, ColoredCard::Draw).can_be_played_on(&top_card));
+        assert!(Card::Wild.can_be_played_on(&top_card));
+        assert!(Card::WildDraw.can_be_played_on(&top_card));
+    }
 }
@@ -1,43 +1,15 @@
-use strum::EnumCount;
+pub(crate) const MIN_PLAYERS: usize = 2;
+pub(crate) const MAX_PLAYERS: usize = 30;
+pub(crate) const INITIAL_HAND_SIZE: usize = 7;
 
-use crate::card::CardColor;
+/// When the rules don't set how many decks to play with, one deck is added for every this many
+/// players.
+pub(crate) const PLAYERS_PER_DECK: usize = 6;
 
-pub(crate) const NUMBER_CARDS_PER_COLOR: &[u8] =
-    &[0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9];
-pub(crate) const SKIP_CARDS_PER_COLOR: u8 = 2;
-pub(crate) const REVERSE_CARDS_PER_COLOR: u8 = 2;
-pub(crate) const DRAW_CARDS_PER_COLOR: u8 = 2;
+/// Drawing for the first turn gives up on breaking a tie after this many rounds, which custom decks
+/// full of the same card would otherwise never do.
+pub(crate) const MAX_STARTING_DRAW_ROUNDS: usize = 10;
 
-pub(crate) const NUMBER_CARDS_IN_DECK: u8 = (NUMBER_CARDS_PER_COLOR.len() * CardColor::COUNT) as u8;
-pub(crate) const SKIP_CARDS_IN_DECK: u8 = SKIP_CARDS_PER_COLOR * CardColor::COUNT as u8;
-pub(crate) const REVERSE_CARDS_IN_DECK: u8 = REVERSE_CARDS_PER_COLOR * CardColor::COUNT as u8;
-pub(crate) const DRAW_CARDS_IN_DECK: u8 = DRAW_CARDS_PER_COLOR * CardColor::COUNT as u8;
-
-pub(crate) const WILD_CARDS_IN_DECK: u8 = 4;
-pub(crate) const WILD_DRAW_CARDS_IN_DECK: u8 = 4;
-
-pub(crate) const TOTAL_CARDS_IN_DECK: u8 = NUMBER_CARDS_IN_DECK
-    + SKIP_CARDS_IN_DECK
-    + REVERSE_CARDS_IN_DECK
-    + DRAW_CARDS_IN_DECK
-    + WILD_CARDS_IN_DECK
-    + WILD_DRAW_CARDS_IN_DECK;
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn correct_card_count_constants() {
-        assert_eq!(NUMBER_CARDS_PER_COLOR.len(), 19);
-        assert_eq!(NUMBER_CARDS_IN_DECK, 76);
-
-        assert_eq!(SKIP_CARDS_IN_DECK, 8);
-
-        assert_eq!(REVERSE_CARDS_IN_DECK, 8);
-
-        assert_eq!(DRAW_CARDS_IN_DECK, 8);
-
-        assert_eq!(TOTAL_CARDS_IN_DECK, 108);
-    }
-}
+/// The most cards a match may be played with, counting every copy of the deck. That is far more
+/// than even the most players need, while keeping a custom deck from taking up all the memory.
+pub(crate) const MAX_DECK_CARDS: u64 = 10_000;
@@ -1,66 +1,55 @@
-use rand::{seq::SliceRandom, thread_rng};
-use strum::IntoEnumIterator;
+use rand::{seq::SliceRandom, Rng};
 
 use crate::{
-    card::{Card, CardColor, ColoredCard},
-    constants::*,
+    card::{Card, CardId, DeckCard},
+    deck_spec::DeckSpec,
 };
 
 #[derive(Debug)]
-pub struct Deck(pub(crate) Vec<Card>);
+pub struct Deck(pub(crate) Vec<DeckCard>);
 
 impl Deck {
     pub fn new() -> Self {
-        let mut cards = Vec::with_capacity(TOTAL_CARDS_IN_DECK.into());
-
-        // Colored Cards
-        for color in CardColor::iter() {
-            // Skip Cards
-            for _ in 0..SKIP_CARDS_PER_COLOR {
-                cards.push(Card::Colored(color.clone(), ColoredCard::Skip));
-            }
-
-            // Reverse Cards
-            for _ in 0..REVERSE_CARDS_PER_COLOR {
-                cards.push(Card::Colored(color.clone(), ColoredCard::Reverse));
-            }
-
-            // Draw Cards
-            for _ in 0..DRAW_CARDS_PER_COLOR {
-                cards.push(Card::Colored(color.clone(), ColoredCard::Draw));
-            }
-
-            // Number Cards
-            for number in NUMBER_CARDS_PER_COLOR {
-                cards.push(Card::Colored(color.clone(), ColoredCard::Number(*number)));
-            }
-        }
-
-        for _ in 0..WILD_CARDS_IN_DECK {
-            cards.push(Card::Wild);
-        }
-
-        for _ in 0..WILD_DRAW_CARDS_IN_DECK {
-            cards.push(Card::WildDraw);
-        }
+        Self::from_spec(&DeckSpec::default(), 1)
+    }
 
-        Self(cards)
+    /// Builds a deck out of `deck_count` copies of the cards in `spec`.
+    pub fn from_spec(spec: &DeckSpec, deck_count: u32) -> Self {
+        let cards = spec.cards();
+        let cards = (0..deck_count).flat_map(|_| cards.iter().cloned());
+
+        // Ids are assigned before shuffling so every card keeps the same id for the whole match
+        Self(
+            cards
+                .enumerate()
+                .map(|(id, card)| DeckCard {
+                    id: id as CardId,
+                    card,
+                })
+                .collect(),
+        )
     }
 
-    pub(crate) fn shuffle(&mut self) {
-        let mut rng = thread_rng();
-        self.0.shuffle(&mut rng);
+    pub(crate) fn shuffle(&mut self, rng: &mut impl Rng) {
+        self.0.shuffle(rng);
     }
 
-    pub(crate) fn draw_cards(&mut self, count: usize) -> Vec<Card> {
+    /// Draws `count` cards, or as many as there are left.
+    pub(crate) fn draw_cards(&mut self, count: usize) -> Vec<DeckCard> {
+        let count = count.min(self.0.len());
         self.0.drain(0..count).collect::<Vec<_>>()
     }
 
-    pub(crate) fn draw_colored_card(&mut self) -> Option<Card> {
-        self.0
-            .iter()
-            .position(|x| matches!(x, Card::Colored(_, _)))
-            .map(|pos| self.0.remove(pos))
+    /// Flips the card the match starts with. A Wild Draw (+4) is not allowed to start with, so
+    /// it goes back into the deck, which is shuffled before flipping again.
+    pub(crate) fn draw_starting_card(&mut self, rng: &mut impl Rng) -> Option<DeckCard> {
+        if self.0.iter().all(|x| x.card == Card::WildDraw) {
+            return None;
+        }
+        while self.0[0].card == Card::WildDraw {
+            self.shuffle(rng);
+        }
+        Some(self.0.remove(0))
     }
 
     pub(crate) fn cards_count(&self) -> usize {
@@ -80,6 +69,26 @@ mod tests {
 
     #[test]
     fn correct_card_count_new_deck() {
-        assert_eq!(Deck::new().cards_count(), TOTAL_CARDS_IN_DECK as usize);
+        assert_eq!(Deck::new().cards_count(), 108);
+    }
+
+    #[test]
+    fn correct_card_count_multiple_decks() {
+        assert_eq!(
+            Deck::from_spec(&DeckSpec::default(), 3).cards_count(),
+            3 * 108
+        );
+    }
+
+    #[test]
+    fn card_ids_are_unique() {
+        let mut deck = Deck::from_spec(&DeckSpec::default(), 2);
+        deck.shuffle(&mut rand::thread_rng());
+
+        let mut ids = deck.0.iter().map(|x| x.id).collect::<Vec<_>>();
+        ids.sort_unstable();
+        ids.dedup();
+
+        assert_eq!(ids.len(), 2 * 108);
     }
 }
@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+use strum::IntoEnumIterator;
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardColor, ColoredCard},
+    constants::MAX_DECK_CARDS,
+};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum DeckSpecError {
+    #[error("Invalid TOML deck: {0}")]
+    Toml(String),
+    #[error("Invalid JSON deck: {0}")]
+    Json(String),
+    #[error("The deck needs at least one colored card to start the match with")]
+    NoColoredCards,
+    #[error("The deck has too many cards")]
+    TooManyCards,
+}
+
+/// How many cards of each kind a single color has.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorSpec {
+    /// The number of cards for every number, from 0 to 9.
+    pub numbers: [u32; 10],
+    pub skip: u32,
+    pub reverse: u32,
+    pub draw: u32,
+}
+
+impl ColorSpec {
+    pub fn cards_count(&self) -> u64 {
+        self.numbers.iter().map(|x| u64::from(*x)).sum::<u64>()
+            + u64::from(self.skip)
+            + u64::from(self.reverse)
+            + u64::from(self.draw)
+    }
+}
+
+impl Default for ColorSpec {
+    fn default() -> Self {
+        Self {
+            numbers: [1, 2, 2, 2, 2, 2, 2, 2, 2, 2],
+            skip: 2,
+            reverse: 2,
+            draw: 2,
+        }
+    }
+}
+
+/// Describes which cards a deck is made of. The default is the standard 108 card deck, and
+/// anything left out when loading a deck falls back to it, e.g. this TOML deck only drops the
+/// Wild Draw cards:
+///
+/// ```toml
+/// wild_draw = 0
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DeckSpec {
+    pub red: ColorSpec,
+    pub green: ColorSpec,
+    pub blue: ColorSpec,
+    pub yellow: ColorSpec,
+    pub wild: u32,
+    pub wild_draw: u32,
+}
+
+impl Default for DeckSpec {
+    fn default() -> Self {
+        Self {
+            red: ColorSpec::default(),
+            green: ColorSpec::default(),
+            blue: ColorSpec::default(),
+            yellow: ColorSpec::default(),
+            wild: 4,
+            wild_draw: 4,
+        }
+    }
+}
+
+impl DeckSpec {
+    pub fn from_toml(s: &str) -> Result<Self, DeckSpecError> {
+        let spec: Self = toml::from_str(s).map_err(|x| DeckSpecError::Toml(x.to_string()))?;
+        spec.validate(1)?;
+        Ok(spec)
+    }
+
+    pub fn from_json(s: &str) -> Result<Self, DeckSpecError> {
+        let spec: Self = serde_json::from_str(s).map_err(|x| DeckSpecError::Json(x.to_string()))?;
+        spec.validate(1)?;
+        Ok(spec)
+    }
+
+    pub fn color(&self, color: CardColor) -> &ColorSpec {
+        match color {
+            CardColor::Red => &self.red,
+            CardColor::Green => &self.green,
+            CardColor::Blue => &self.blue,
+            CardColor::Yellow => &self.yellow,
+        }
+    }
+
+    fn colored_cards_count(&self) -> u64 {
+        CardColor::iter()
+            .map(|color| self.color(color).cards_count())
+            .sum()
+    }
+
+    pub fn cards_count(&self) -> u64 {
+        self.colored_cards_count() + u64::from(self.wild) + u64::from(self.wild_draw)
+    }
+
+    /// Makes sure a match can be played with `deck_count` copies of the deck. Whether that is
+    /// enough cards for everyone is only known once the players are, so that is checked when the
+    /// match is created.
+    pub fn validate(&self, deck_count: u32) -> Result<(), DeckSpecError> {
+        if self.colored_cards_count() == 0 {
+            return Err(DeckSpecError::NoColoredCards);
+        }
+        if self.cards_count().saturating_mul(u64::from(deck_count)) > MAX_DECK_CARDS {
+            return Err(DeckSpecError::TooManyCards);
+        }
+        Ok(())
+    }
+
+    /// Lists every card of the deck, always in the same order.
+    pub fn cards(&self) -> Vec<Card> {
+        let mut cards = Vec::new();
+
+        for color in CardColor::iter() {
+            let spec = self.color(color);
+            for (card, count) in [
+                (ColoredCard::Skip, spec.skip),
+                (ColoredCard::Reverse, spec.reverse),
+                (ColoredCard::Draw, spec.draw),
+            ] {
+                for _ in 0..count {
+                    cards.push(Card::Colored(color, card.clone()));
+                }
+            }
+            for (number, count) in (0..).zip(spec.numbers) {
+                for _ in 0..count {
+                    cards.push(Card::Colored(color, ColoredCard::Number(number)));
+                }
+            }
+        }
+
+        for _ in 0..self.wild {
+            cards.push(Card::Wild);
+        }
+        for _ in 0..self.wild_draw {
+            cards.push(Card::WildDraw);
+        }
+
+        cards
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn correct_card_count_standard_deck() {
+        let spec = DeckSpec::default();
+
+        assert_eq!(spec.red.cards_count(), 25);
+        assert_eq!(spec.colored_cards_count(), 100);
+        assert_eq!(spec.cards_count(), 108);
+        assert_eq!(spec.cards().len(), 108);
+    }
+
+    #[test]
+    fn load_partial_toml_deck() {
+        let spec = DeckSpec::from_toml(
+            r#"
+            wild_draw = 0
+
+            [red]
+            skip = 4
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(spec.wild_draw, 0);
+        assert_eq!(spec.red.skip, 4);
+        assert_eq!(spec.red.reverse, 2);
+        assert_eq!(spec.green, ColorSpec::default());
+        assert_eq!(spec.cards_count(), 106);
+    }
+
+    #[test]
+    fn load_json_deck() {
+        let spec =
+            DeckSpec::from_json(r#"{ "blue": { "numbers": [0, 0, 0, 0, 0, 0, 0, 0, 0, 1] } }"#)
+                .unwrap();
+
+        assert_eq!(spec.blue.cards_count(), 7);
+        assert_eq!(spec.cards_count(), 108 - 18);
+    }
+
+    #[test]
+    fn reject_invalid_decks() {
+        assert!(matches!(
+            DeckSpec::from_toml("wilds = 4"),
+            Err(DeckSpecError::Toml(_))
+        ));
+        assert!(matches!(
+            DeckSpec::from_json("{ \"wild\": -1 }"),
+            Err(DeckSpecError::Json(_))
+        ));
+
+        let no_colors = ColorSpec {
+            numbers: [0; 10],
+            skip: 0,
+            reverse: 0,
+            draw: 0,
+        };
+        let spec = DeckSpec {
+            red: no_colors.clone(),
+            green: no_colors.clone(),
+            blue: no_colors.clone(),
+            yellow: no_colors,
+            ..Default::default()
+        };
+        assert_eq!(spec.validate(1), Err(DeckSpecError::NoColoredCards));
+
+        let spec = DeckSpec {
+            wild: u32::MAX,
+            ..Default::default()
+        };
+        assert_eq!(spec.validate(1), Err(DeckSpecError::TooManyCards));
+        assert_eq!(
+            DeckSpec::default().validate(u32::MAX),
+            Err(DeckSpecError::TooManyCards)
+        );
+    }
+
+    #[test]
+    fn validate_limits_cards_of_all_decks() {
+        let spec = DeckSpec {
+            wild: (MAX_DECK_CARDS - 104) as u32,
+            ..Default::default()
+        };
+        assert_eq!(spec.cards_count(), MAX_DECK_CARDS);
+        assert_eq!(spec.validate(1), Ok(()));
+        assert_eq!(spec.validate(2), Err(DeckSpecError::TooManyCards));
+
+        assert_eq!(DeckSpec::default().validate(92), Ok(()));
+        assert_eq!(
+            DeckSpec::default().validate(93),
+            Err(DeckSpecError::TooManyCards)
+        );
+    }
+}
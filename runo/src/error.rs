@@ -2,12 +2,26 @@ use std::fmt::Debug;
 
 use thiserror::Error;
 
-#[derive(Error, Debug)]
+use crate::deck_spec::DeckSpecError;
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
 pub enum UnoError {
     #[error("Not enough players")]
     NotEnoughPlayers,
     #[error("Too many players")]
     TooManyPlayers,
+    #[error("Player is already in the match")]
+    PlayerAlreadyExists,
+    #[error("Player is not in the match")]
+    PlayerNotFound,
+    #[error("Not enough cards left in the deck")]
+    NotEnoughCards,
+    #[error("Teams must be at least two teams of the same size with every player in one of them")]
+    InvalidTeams,
+    #[error("Players can't join a team match that already started")]
+    TeamMatchStarted,
+    #[error(transparent)]
+    InvalidDeck(#[from] DeckSpecError),
 }
 
 pub type Result<T, E = UnoError> = std::result::Result<T, E>;
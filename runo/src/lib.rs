@@ -1,7 +1,11 @@
 pub mod card;
 pub mod constants;
 pub mod deck;
+pub mod deck_spec;
 pub mod error;
+pub mod notation;
 pub mod player;
+pub mod record;
+pub mod rules;
 pub mod turn;
 pub mod uno;
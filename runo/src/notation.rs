@@ -0,0 +1,331 @@
+//! A compact text notation for cards and turn actions, e.g. `R5`, `GS`, `BR`, `Y+2`, `W` and
+//! `W+4:R`, meant for command input, logs and test fixtures.
+//!
+//! Colors are written as their first letter. Played wild cards are followed by `:` and the
+//! chosen color. Turn actions are `#<card id>` to play a card, with `:<color>` for wild cards
+//! and a trailing `!` to say UNO with it, `D` to draw, `C` to call someone out, `U` to say UNO,
+//! `CH` to challenge a Wild Draw (+4), `SW<player id>` to swap hands after playing a 7 and
+//! `CC:<color>` to choose the color of a starting wild card. Parsing ignores case and
+//! surrounding whitespace.
+
+use std::str::FromStr;
+
+use thiserror::Error;
+
+use crate::{
+    card::{Card, CardColor, ColoredCard, PlayedCard},
+    turn::{PlayAction, TurnAction},
+};
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+#[error("`{0}` is not valid notation")]
+pub struct ParseNotationError(String);
+
+/// Formats a value in the compact notation, which its `FromStr` implementation parses back.
+pub trait Notation {
+    fn notation(&self) -> String;
+}
+
+impl Notation for CardColor {
+    fn notation(&self) -> String {
+        match self {
+            CardColor::Red => "R",
+            CardColor::Green => "G",
+            CardColor::Blue => "B",
+            CardColor::Yellow => "Y",
+        }
+        .to_string()
+    }
+}
+
+impl Notation for ColoredCard {
+    fn notation(&self) -> String {
+        match self {
+            ColoredCard::Number(number) => number.to_string(),
+            ColoredCard::Skip => "S".to_string(),
+            ColoredCard::Reverse => "R".to_string(),
+            ColoredCard::Draw => "+2".to_string(),
+        }
+    }
+}
+
+impl Notation for Card {
+    fn notation(&self) -> String {
+        match self {
+            Card::Colored(color, card) => format!("{}{}", color.notation(), card.notation()),
+            Card::Wild => "W".to_string(),
+            Card::WildDraw => "W+4".to_string(),
+        }
+    }
+}
+
+impl Notation for PlayedCard {
+    fn notation(&self) -> String {
+        match self {
+            PlayedCard::Colored(color, card) => Card::Colored(*color, card.clone()).notation(),
+            PlayedCard::Wild(color) => format!("{}:{}", Card::Wild.notation(), color.notation()),
+            PlayedCard::WildDraw(color) => {
+                format!("{}:{}", Card::WildDraw.notation(), color.notation())
+            }
+        }
+    }
+}
+
+impl Notation for TurnAction {
+    fn notation(&self) -> String {
+        match self {
+            TurnAction::Play(PlayAction::Card {
+                card_id,
+                chosen_color,
+                uno,
+            }) => {
+                let color = chosen_color.map(|x| format!(":{}", x.notation()));
+                let uno = if *uno { "!" } else { "" };
+                format!("#{card_id}{}{uno}", color.unwrap_or_default())
+            }
+            TurnAction::Draw => "D".to_string(),
+            TurnAction::Callout => "C".to_string(),
+            TurnAction::Uno => "U".to_string(),
+            TurnAction::Challenge => "CH".to_string(),
+            TurnAction::SwapHands(player_id) => format!("SW{player_id}"),
+            TurnAction::ChooseColor(color) => format!("CC:{}", color.notation()),
+        }
+    }
+}
+
+fn parse_color(s: &str) -> Option<CardColor> {
+    match s {
+        "R" => Some(CardColor::Red),
+        "G" => Some(CardColor::Green),
+        "B" => Some(CardColor::Blue),
+        "Y" => Some(CardColor::Yellow),
+        _ => None,
+    }
+}
+
+fn parse_colored_card(s: &str) -> Option<ColoredCard> {
+    match s {
+        "S" => Some(ColoredCard::Skip),
+        "R" => Some(ColoredCard::Reverse),
+        "+2" => Some(ColoredCard::Draw),
+        _ if s.len() == 1 => s.parse().ok().map(ColoredCard::Number),
+        _ => None,
+    }
+}
+
+/// Parses a card without a chosen color, `s` is already uppercase and trimmed.
+fn parse_card(s: &str) -> Option<Card> {
+    match s {
+        "W" => Some(Card::Wild),
+        "W+4" => Some(Card::WildDraw),
+        _ => {
+            let color = parse_color(s.get(..1)?)?;
+            let card = parse_colored_card(s.get(1..)?)?;
+            Some(Card::Colored(color, card))
+        }
+    }
+}
+
+fn parse_played_card(s: &str) -> Option<PlayedCard> {
+    match s.split_once(':') {
+        Some((card, color)) => {
+            let color = parse_color(color)?;
+            match parse_card(card)? {
+                Card::Wild => Some(PlayedCard::Wild(color)),
+                Card::WildDraw => Some(PlayedCard::WildDraw(color)),
+                Card::Colored(_, _) => None,
+            }
+        }
+        None => match parse_card(s)? {
+            Card::Colored(color, card) => Some(PlayedCard::Colored(color, card)),
+            Card::Wild | Card::WildDraw => None,
+        },
+    }
+}
+
+fn parse_turn_action(s: &str) -> Option<TurnAction> {
+    match s {
+        "D" => Some(TurnAction::Draw),
+        "C" => Some(TurnAction::Callout),
+        "U" => Some(TurnAction::Uno),
+        "CH" => Some(TurnAction::Challenge),
+        _ if s.starts_with("CC:") => Some(TurnAction::ChooseColor(parse_color(&s[3..])?)),
+        _ if s.starts_with("SW") => Some(TurnAction::SwapHands(s[2..].parse().ok()?)),
+        _ => {
+            let play = s.strip_prefix('#')?;
+            let (play, uno) = match play.strip_suffix('!') {
+                Some(play) => (play, true),
+                None => (play, false),
+            };
+            let (card_id, chosen_color) = match play.split_once(':') {
+                Some((card_id, color)) => (card_id, Some(parse_color(color)?)),
+                None => (play, None),
+            };
+            Some(TurnAction::Play(PlayAction::Card {
+                card_id: card_id.parse().ok()?,
+                chosen_color,
+                uno,
+            }))
+        }
+    }
+}
+
+fn parse<T>(s: &str, parser: impl FnOnce(&str) -> Option<T>) -> Result<T, ParseNotationError> {
+    parser(&s.trim().to_uppercase()).ok_or_else(|| ParseNotationError(s.to_string()))
+}
+
+impl FromStr for Card {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, parse_card)
+    }
+}
+
+impl FromStr for PlayedCard {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, parse_played_card)
+    }
+}
+
+impl FromStr for TurnAction {
+    type Err = ParseNotationError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s, parse_turn_action)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use strum::IntoEnumIterator;
+
+    use super::*;
+
+    fn all_cards() -> Vec<Card> {
+        let mut cards = vec![Card::Wild, Card::WildDraw];
+        for color in CardColor::iter() {
+            for number in 0..=9 {
+                cards.push(Card::Colored(color, ColoredCard::Number(number)));
+            }
+            for card in [ColoredCard::Skip, ColoredCard::Reverse, ColoredCard::Draw] {
+                cards.push(Card::Colored(color, card));
+            }
+        }
+        cards
+    }
+
+    #[test]
+    fn format_cards() {
+        let red_5 = Card::Colored(CardColor::Red, ColoredCard::Number(5));
+        assert_eq!(red_5.notation(), "R5");
+        assert_eq!(
+            Card::Colored(CardColor::Green, ColoredCard::Skip).notation(),
+            "GS"
+        );
+        assert_eq!(
+            Card::Colored(CardColor::Blue, ColoredCard::Reverse).notation(),
+            "BR"
+        );
+        assert_eq!(
+            Card::Colored(CardColor::Yellow, ColoredCard::Draw).notation(),
+            "Y+2"
+        );
+        assert_eq!(Card::Wild.notation(), "W");
+        assert_eq!(Card::WildDraw.notation(), "W+4");
+        assert_eq!(PlayedCard::WildDraw(CardColor::Red).notation(), "W+4:R");
+    }
+
+    #[test]
+    fn cards_round_trip() {
+        for card in all_cards() {
+            assert_eq!(card.notation().parse(), Ok(card));
+        }
+    }
+
+    #[test]
+    fn played_cards_round_trip() {
+        for color in CardColor::iter() {
+            for played_card in [
+                PlayedCard::Colored(color, ColoredCard::Number(7)),
+                PlayedCard::Colored(color, ColoredCard::Draw),
+                PlayedCard::Wild(color),
+                PlayedCard::WildDraw(color),
+            ] {
+                assert_eq!(played_card.notation().parse(), Ok(played_card));
+            }
+        }
+    }
+
+    #[test]
+    fn turn_actions_round_trip() {
+        let actions = [
+            TurnAction::Play(PlayAction::Card {
+                card_id: 12,
+                chosen_color: None,
+                uno: false,
+            }),
+            TurnAction::Play(PlayAction::Card {
+                card_id: 107,
+                chosen_color: Some(CardColor::Yellow),
+                uno: false,
+            }),
+            TurnAction::Play(PlayAction::Card {
+                card_id: 8,
+                chosen_color: None,
+                uno: true,
+            }),
+            TurnAction::Play(PlayAction::Card {
+                card_id: 9,
+                chosen_color: Some(CardColor::Green),
+                uno: true,
+            }),
+            TurnAction::Draw,
+            TurnAction::Callout,
+            TurnAction::Uno,
+            TurnAction::Challenge,
+            TurnAction::SwapHands(u64::MAX),
+            TurnAction::ChooseColor(CardColor::Green),
+        ];
+        for action in actions {
+            assert_eq!(action.notation().parse(), Ok(action));
+        }
+    }
+
+    #[test]
+    fn parse_ignores_case_and_whitespace() {
+        assert_eq!(
+            " y+2 ".parse(),
+            Ok(Card::Colored(CardColor::Yellow, ColoredCard::Draw))
+        );
+        assert_eq!("w+4:g".parse(), Ok(PlayedCard::WildDraw(CardColor::Green)));
+        assert_eq!(
+            "#3:b".parse(),
+            Ok(TurnAction::Play(PlayAction::Card {
+                card_id: 3,
+                chosen_color: Some(CardColor::Blue),
+                uno: false,
+            }))
+        );
+    }
+
+    #[test]
+    fn reject_invalid_notation() {
+        for s in ["", "X5", "R10", "R+4", "W:R", "RS:G", "W+2"] {
+            assert_eq!(
+                s.parse::<Card>(),
+                Err(ParseNotationError(s.to_string())),
+                "{s}"
+            );
+        }
+        // Played wild cards need a color and colored ones must not have one
+        for s in ["W", "W+4", "R5:G", "W:X"] {
+            assert!(s.parse::<PlayedCard>().is_err(), "{s}");
+        }
+        for s in ["#", "#x", "#3:", "P3", "DD", "SW", "SW-1", "CC", "CC:W"] {
+            assert!(s.parse::<TurnAction>().is_err(), "{s}");
+        }
+    }
+}
@@ -1,46 +1,64 @@
-use crate::card::Card;
+use crate::card::{CardId, DeckCard};
 
-#[allow(unused)]
 #[derive(Debug)]
 pub struct Player {
     pub id: u64,
     name: String,
-    pub hand: Vec<Card>,
+    pub hand: Vec<DeckCard>,
     pub uno_performed: bool,
+    /// Whether the next player acted after this player got down to one card, so that they can no
+    /// longer be called out for not saying UNO.
+    pub callout_window_passed: bool,
 }
 
 impl Player {
-    pub fn new(id: u64, name: String, cards: Vec<Card>) -> Self {
+    pub fn new(id: u64, name: String, cards: Vec<DeckCard>) -> Self {
         Self {
             id,
             name,
             hand: cards,
             uno_performed: false,
+            callout_window_passed: false,
         }
     }
 
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     pub fn cards_count(&self) -> usize {
         self.hand.len()
     }
 
-    pub fn card_index(&self, card: &Card) -> Option<usize> {
-        self.hand.iter().position(|x| x == card)
+    pub fn card_index(&self, card_id: CardId) -> Option<usize> {
+        self.hand.iter().position(|x| x.id == card_id)
+    }
+
+    pub fn get_card(&self, card_id: CardId) -> Option<&DeckCard> {
+        self.hand.iter().find(|x| x.id == card_id)
     }
 
-    pub fn add_card(&mut self, card: Card) {
+    pub fn add_card(&mut self, card: DeckCard) {
         self.hand.push(card);
         self.uno_performed = false;
+        self.callout_window_passed = false;
     }
 
     pub fn remove_card(&mut self, index: usize) {
         self.hand.remove(index);
         self.uno_performed = false;
+        self.callout_window_passed = false;
     }
 
     pub fn uno(&mut self) {
         self.uno_performed = true;
     }
 
+    /// Whether the player is down to one card without having said UNO in time.
+    pub fn can_be_called_out(&self) -> bool {
+        self.cards_count() == 1 && !self.uno_performed && !self.callout_window_passed
+    }
+
     // pub fn uno_performed(&self) -> bool {
     //     self.uno_performed
     // }
@@ -0,0 +1,687 @@
+//! A portable text record of a match, from which the exact same match can be rebuilt.
+//!
+//! A record starts with a header of tags, followed by one line per entry:
+//!
+//! ```text
+//! [Seed "11725087193384342921"]
+//! [Seat "0 Alice"]
+//! [Seat "1 Bob"]
+//!
+//! 0 #12 ; R5
+//! 1 D
+//! join 2 "Carol"
+//! 0 #104:G ; W+4
+//! leave 1
+//! skip
+//! ```
+//!
+//! The header holds the seed the match was created with, the [rules](crate::rules::Rules) as JSON
+//! in a `Rules` tag unless they are the standard ones, the `Host` and `Dealer` of the match if
+//! it had them, one seat per player the match started with and, in a team match, one `Team` tag
+//! per team with the ids of its players. Entries are turns, written as the id of the player that
+//! took the turn and the action in [notation](crate::notation), players joining or leaving and
+//! turns being skipped. Only turns the match accepted are recorded. Everything after a `;` is a
+//! comment, which is used to show the card that was played. Names are quoted, with `\"` and `\\`
+//! as escapes.
+
+use std::{fmt, str::FromStr};
+
+use thiserror::Error;
+
+use crate::{
+    deck::Deck,
+    error::UnoError,
+    notation::Notation,
+    rules::Rules,
+    turn::{PlayAction, TurnAction, TurnActionResult},
+    uno::Uno,
+};
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RecordEntry {
+    Turn { player_id: u64, action: TurnAction },
+    Join { player_id: u64, player_name: String },
+    Leave { player_id: u64 },
+    SkipTurn,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    pub seed: u64,
+    pub rules: Rules,
+    pub host_id: Option<u64>,
+    pub dealer_id: Option<u64>,
+    pub seats: Vec<(u64, String)>,
+    pub teams: Vec<Vec<u64>>,
+    pub entries: Vec<RecordEntry>,
+}
+
+#[derive(Error, Clone, Debug, PartialEq, Eq)]
+pub enum RecordError {
+    #[error("Line {line}: {reason}")]
+    Syntax { line: usize, reason: String },
+    #[error("The record has no seed")]
+    MissingSeed,
+    #[error("The match could not be created: {0}")]
+    Setup(#[from] UnoError),
+    #[error("Entry {entry}: it is not player {player_id}'s turn")]
+    NotTheirTurn { entry: usize, player_id: u64 },
+    #[error("Entry {entry}: the turn of player {player_id} was refused with {result:?}")]
+    TurnRejected {
+        entry: usize,
+        player_id: u64,
+        result: TurnActionResult,
+    },
+    #[error("Entry {entry}: there are no players left")]
+    NoPlayersLeft { entry: usize },
+    #[error("Entry {entry}: {error}")]
+    Rejected { entry: usize, error: UnoError },
+}
+
+impl GameRecord {
+    /// Rebuilds the match by creating it from the header and then applying every entry in order.
+    ///
+    /// Records may come from anywhere, so the deck is checked before the match is set up and any
+    /// entry the match refuses fails the replay.
+    pub fn replay(&self) -> Result<Uno, RecordError> {
+        let mut uno = Uno::new_with_seats(
+            self.seats.clone(),
+            self.seed,
+            self.rules.clone(),
+            self.host_id,
+            self.dealer_id,
+        )?;
+        if !self.teams.is_empty() {
+            uno.assign_teams(self.teams.clone())?;
+        }
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            let entry_number = index + 1;
+            match entry {
+                RecordEntry::Turn { player_id, action } => {
+                    let result = uno.play_turn(*player_id, action.clone());
+                    match result.turn_action_result {
+                        TurnActionResult::NotYourTurn => {
+                            return Err(RecordError::NotTheirTurn {
+                                entry: entry_number,
+                                player_id: *player_id,
+                            });
+                        }
+                        result if result.is_rejected() => {
+                            return Err(RecordError::TurnRejected {
+                                entry: entry_number,
+                                player_id: *player_id,
+                                result,
+                            });
+                        }
+                        _ => {}
+                    }
+                }
+                RecordEntry::Join {
+                    player_id,
+                    player_name,
+                } => uno
+                    .add_player(*player_id, player_name.clone())
+                    .map_err(|error| RecordError::Rejected {
+                        entry: entry_number,
+                        error,
+                    })?,
+                RecordEntry::Leave { player_id } => {
+                    uno.remove_player(player_id)
+                        .map_err(|error| RecordError::Rejected {
+                            entry: entry_number,
+                            error,
+                        })?;
+                }
+                RecordEntry::SkipTurn => {
+                    if uno.get_player_ids().is_empty() {
+                        return Err(RecordError::NoPlayersLeft {
+                            entry: entry_number,
+                        });
+                    }
+                    uno.skip_turn();
+                }
+            }
+        }
+
+        Ok(uno)
+    }
+}
+
+fn write_quoted(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    write!(f, "\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl fmt::Display for GameRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[Seed ")?;
+        write_quoted(f, &self.seed.to_string())?;
+        writeln!(f, "]")?;
+        if self.rules != Rules::default() {
+            let rules = serde_json::to_string(&self.rules).map_err(|_| fmt::Error)?;
+            write!(f, "[Rules ")?;
+            write_quoted(f, &rules)?;
+            writeln!(f, "]")?;
+        }
+        for (tag, player_id) in [("Host", self.host_id), ("Dealer", self.dealer_id)] {
+            if let Some(player_id) = player_id {
+                write!(f, "[{tag} ")?;
+                write_quoted(f, &player_id.to_string())?;
+                writeln!(f, "]")?;
+            }
+        }
+        for (player_id, player_name) in &self.seats {
+            write!(f, "[Seat ")?;
+            write_quoted(f, &format!("{player_id} {player_name}"))?;
+            writeln!(f, "]")?;
+        }
+        for team in &self.teams {
+            let player_ids = team.iter().map(u64::to_string).collect::<Vec<_>>();
+            write!(f, "[Team ")?;
+            write_quoted(f, &player_ids.join(" "))?;
+            writeln!(f, "]")?;
+        }
+        writeln!(f)?;
+
+        // Card ids are assigned before shuffling, so they always refer to the same card. Records
+        // with an invalid deck are written without naming the cards, as it can't be built.
+        let deck_count = self.rules.deck_count(self.seats.len());
+        let deck = match self.rules.deck.validate(deck_count) {
+            Ok(()) => Deck::from_spec(&self.rules.deck, deck_count),
+            Err(_) => Deck(Vec::new()),
+        };
+        for entry in &self.entries {
+            match entry {
+                RecordEntry::Turn { player_id, action } => {
+                    write!(f, "{player_id} {}", action.notation())?;
+                    if let TurnAction::Play(PlayAction::Card { card_id, .. }) = action {
+                        if let Some(card) = deck.0.iter().find(|x| x.id == *card_id) {
+                            write!(f, " ; {}", card.card.notation())?;
+                        }
+                    }
+                }
+                RecordEntry::Join {
+                    player_id,
+                    player_name,
+                } => {
+                    write!(f, "join {player_id} ")?;
+                    write_quoted(f, player_name)?;
+                }
+                RecordEntry::Leave { player_id } => write!(f, "leave {player_id}")?,
+                RecordEntry::SkipTurn => write!(f, "skip")?,
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Parses a quoted string at the start of `s`, returning it and whatever follows it.
+fn parse_quoted(s: &str) -> Result<(String, &str), String> {
+    let mut chars = s
+        .strip_prefix('"')
+        .ok_or("expected a quoted string")?
+        .char_indices();
+    let mut value = String::new();
+    while let Some((index, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &s[index + 2..])),
+            '\\' => match chars.next() {
+                Some((_, c @ ('"' | '\\'))) => value.push(c),
+                _ => return Err("invalid escape".to_string()),
+            },
+            c => value.push(c),
+        }
+    }
+    Err("unterminated quoted string".to_string())
+}
+
+/// Makes sure nothing but a comment is left on the line.
+fn expect_end(rest: &str) -> Result<(), String> {
+    let rest = rest.trim();
+    if rest.is_empty() || rest.starts_with(';') {
+        Ok(())
+    } else {
+        Err(format!("unexpected `{rest}`"))
+    }
+}
+
+fn parse_player_id(s: &str) -> Result<u64, String> {
+    s.parse().map_err(|_| format!("invalid player id `{s}`"))
+}
+
+fn parse_tag(line: &str) -> Result<(&str, String), String> {
+    let inner = line.strip_prefix('[').ok_or("expected a tag")?;
+    let (name, rest) = inner.split_once(' ').ok_or("expected a tag value")?;
+    let (value, rest) = parse_quoted(rest.trim_start())?;
+    let rest = rest.trim_start().strip_prefix(']').ok_or("expected `]`")?;
+    expect_end(rest)?;
+    Ok((name, value))
+}
+
+fn parse_entry(line: &str) -> Result<RecordEntry, String> {
+    let (first, rest) = line.split_once(' ').unwrap_or((line, ""));
+    let rest = rest.trim_start();
+    match first {
+        "join" => {
+            let (player_id, rest) = rest.split_once(' ').ok_or("expected a player name")?;
+            let (player_name, rest) = parse_quoted(rest.trim_start())?;
+            expect_end(rest)?;
+            Ok(RecordEntry::Join {
+                player_id: parse_player_id(player_id)?,
+                player_name,
+            })
+        }
+        "leave" => {
+            let (player_id, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+            expect_end(rest)?;
+            Ok(RecordEntry::Leave {
+                player_id: parse_player_id(player_id)?,
+            })
+        }
+        "skip" => {
+            expect_end(rest)?;
+            Ok(RecordEntry::SkipTurn)
+        }
+        player_id => {
+            let (action, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+            expect_end(rest)?;
+            Ok(RecordEntry::Turn {
+                player_id: parse_player_id(player_id)?,
+                action: action.parse().map_err(|x| format!("{x}"))?,
+            })
+        }
+    }
+}
+
+impl FromStr for GameRecord {
+    type Err = RecordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut seed = None;
+        let mut rules = Rules::default();
+        let mut host_id = None;
+        let mut dealer_id = None;
+        let mut seats = Vec::new();
+        let mut teams = Vec::new();
+        let mut entries = Vec::new();
+
+        for (index, line) in s.lines().enumerate() {
+            let line = line.trim();
+            let syntax_error = |reason| RecordError::Syntax {
+                line: index + 1,
+                reason,
+            };
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') {
+                if !entries.is_empty() {
+                    return Err(syntax_error("tags must come before entries".to_string()));
+                }
+                match parse_tag(line).map_err(syntax_error)? {
+                    ("Seed", value) => {
+                        let value = value
+                            .parse()
+                            .map_err(|_| syntax_error(format!("invalid seed `{value}`")))?;
+                        seed = Some(value);
+                    }
+                    ("Rules", value) => {
+                        rules = serde_json::from_str(&value)
+                            .map_err(|x| syntax_error(format!("invalid rules: {x}")))?;
+                    }
+                    ("Host", value) => {
+                        host_id = Some(parse_player_id(&value).map_err(syntax_error)?);
+                    }
+                    ("Dealer", value) => {
+                        dealer_id = Some(parse_player_id(&value).map_err(syntax_error)?);
+                    }
+                    ("Seat", value) => {
+                        let (player_id, player_name) = value
+                            .split_once(' ')
+                            .ok_or_else(|| syntax_error("expected a player name".to_string()))?;
+                        let player_id = parse_player_id(player_id).map_err(syntax_error)?;
+                        seats.push((player_id, player_name.to_string()));
+                    }
+                    ("Team", value) => {
+                        let team = value
+                            .split_whitespace()
+                            .map(parse_player_id)
+                            .collect::<Result<_, _>>()
+                            .map_err(syntax_error)?;
+                        teams.push(team);
+                    }
+                    (name, _) => return Err(syntax_error(format!("unknown tag `{name}`"))),
+                }
+            } else {
+                entries.push(parse_entry(line).map_err(syntax_error)?);
+            }
+        }
+
+        Ok(Self {
+            seed: seed.ok_or(RecordError::MissingSeed)?,
+            rules,
+            host_id,
+            dealer_id,
+            seats,
+            teams,
+            entries,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crate::{card::CardColor, deck_spec::DeckSpecError, rules::StartingPlayer};
+
+    use super::*;
+
+    fn create_players_info(count: usize) -> BTreeMap<u64, String> {
+        let mut players_info = BTreeMap::new();
+        for i in 0..count {
+            players_info.insert(i as u64, format!("Player {}", i + 1));
+        }
+        players_info
+    }
+
+    /// Plays the first card of the current player's hand that can be played, picking red for wild
+    /// cards, or draws if there is none.
+    fn play_first_card(uno: &mut Uno) {
+        let player = uno
+            .get_player(&uno.get_current_turn_player_id())
+            .expect("Current player must exist.");
+        let action = match player
+            .hand
+            .iter()
+            .find(|x| x.card.can_be_played_on(uno.get_last_played_card()))
+        {
+            Some(deck_card) => TurnAction::Play(PlayAction::Card {
+                card_id: deck_card.id,
+                chosen_color: Some(CardColor::Red),
+                uno: false,
+            }),
+            None => TurnAction::Draw,
+        };
+        uno.play_turn(uno.get_current_turn_player_id(), action);
+    }
+
+    fn hands(uno: &Uno) -> Vec<(u64, Vec<u32>)> {
+        uno.get_player_ids()
+            .into_iter()
+            .map(|id| {
+                let player = uno.get_player(&id).expect("Player must exist.");
+                (id, player.hand.iter().map(|x| x.id).collect())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn same_seed_creates_same_match() {
+        let first = Uno::new_with_seed(create_players_info(3), 42).unwrap();
+        let second = Uno::new_with_seed(create_players_info(3), 42).unwrap();
+
+        assert_eq!(hands(&first), hands(&second));
+        assert_eq!(first.get_last_played_card(), second.get_last_played_card());
+    }
+
+    #[test]
+    fn exported_record_rebuilds_match() {
+        let mut uno = Uno::new_with_seed(create_players_info(3), 7).unwrap();
+        play_first_card(&mut uno);
+        uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Draw);
+        uno.add_player(10, "Player \"11\"".to_string()).unwrap();
+        play_first_card(&mut uno);
+        uno.remove_player(&1).unwrap();
+        uno.skip_turn();
+        uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Uno);
+
+        let text = uno.record().to_string();
+        let record = text.parse::<GameRecord>().unwrap();
+        assert_eq!(record, uno.record());
+
+        let replayed = record.replay().unwrap();
+        assert_eq!(hands(&replayed), hands(&uno));
+        assert_eq!(replayed.get_last_played_card(), uno.get_last_played_card());
+        assert_eq!(
+            replayed.get_current_turn_player_id(),
+            uno.get_current_turn_player_id()
+        );
+    }
+
+    #[test]
+    fn record_keeps_custom_rules() {
+        let mut rules = Rules::default();
+        rules.deck.wild_draw = 0;
+        rules.deck.red.skip = 5;
+
+        let mut uno = Uno::new_with_rules(create_players_info(2), 3, rules.clone()).unwrap();
+        play_first_card(&mut uno);
+
+        let text = uno.record().to_string();
+        let record = text.parse::<GameRecord>().unwrap();
+        assert_eq!(record.rules, rules);
+
+        let replayed = record.replay().unwrap();
+        assert_eq!(hands(&replayed), hands(&uno));
+    }
+
+    #[test]
+    fn record_keeps_host_and_dealer() {
+        let rules = Rules {
+            starting_player: StartingPlayer::LeftOfDealer,
+            ..Default::default()
+        };
+        let uno = Uno::new_with_seats(
+            create_players_info(3).into_iter().collect(),
+            5,
+            rules,
+            Some(0),
+            Some(2),
+        )
+        .unwrap();
+
+        let text = uno.record().to_string();
+        let record = text.parse::<GameRecord>().unwrap();
+        assert_eq!(record.host_id, Some(0));
+        assert_eq!(record.dealer_id, Some(2));
+
+        let replayed = record.replay().unwrap();
+        assert_eq!(replayed.get_starting_player(), uno.get_starting_player());
+    }
+
+    #[test]
+    fn record_keeps_teams() {
+        let players = create_players_info(4).into_iter().collect::<Vec<_>>();
+        let teams = vec![players[..2].to_vec(), players[2..].to_vec()];
+        let uno = Uno::new_with_teams(teams, 5, Rules::default(), None, None).unwrap();
+
+        let text = uno.record().to_string();
+        assert!(text.contains("[Team \"0 1\"]\n[Team \"2 3\"]"));
+        let record = text.parse::<GameRecord>().unwrap();
+        assert_eq!(record, uno.record());
+
+        let replayed = record.replay().unwrap();
+        assert_eq!(replayed.get_teams(), uno.get_teams());
+        assert_eq!(replayed.get_player_ids(), uno.get_player_ids());
+    }
+
+    #[test]
+    fn parse_documented_example() {
+        let text = r#"
+            [Seed "11725087193384342921"]
+            [Seat "0 Alice"]
+            [Seat "1 Bob"]
+
+            0 #12 ; R5
+            1 D
+            join 2 "Carol"
+            0 #104:G ; W+4
+            leave 1
+            skip
+        "#;
+
+        let record = text.parse::<GameRecord>().unwrap();
+        assert_eq!(record.seed, 11725087193384342921);
+        assert_eq!(
+            record.seats,
+            vec![(0, "Alice".to_string()), (1, "Bob".to_string())]
+        );
+        assert_eq!(
+            record.entries,
+            vec![
+                RecordEntry::Turn {
+                    player_id: 0,
+                    action: TurnAction::Play(PlayAction::Card {
+                        card_id: 12,
+                        chosen_color: None,
+                        uno: false,
+                    }),
+                },
+                RecordEntry::Turn {
+                    player_id: 1,
+                    action: TurnAction::Draw,
+                },
+                RecordEntry::Join {
+                    player_id: 2,
+                    player_name: "Carol".to_string(),
+                },
+                RecordEntry::Turn {
+                    player_id: 0,
+                    action: TurnAction::Play(PlayAction::Card {
+                        card_id: 104,
+                        chosen_color: Some(CardColor::Green),
+                        uno: false,
+                    }),
+                },
+                RecordEntry::Leave { player_id: 1 },
+                RecordEntry::SkipTurn,
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_reports_line_of_error() {
+        let error = "[Seed \"1\"]\n\n0 X".parse::<GameRecord>().unwrap_err();
+        assert!(matches!(error, RecordError::Syntax { line: 3, .. }));
+
+        let error = "[Seat \"0 Alice\"]".parse::<GameRecord>().unwrap_err();
+        assert_eq!(error, RecordError::MissingSeed);
+    }
+
+    #[test]
+    fn replay_fails_if_not_their_turn() {
+        let record = GameRecord {
+            seed: 1,
+            rules: Rules {
+                starting_player: StartingPlayer::Host,
+                ..Default::default()
+            },
+            host_id: Some(0),
+            dealer_id: None,
+            seats: create_players_info(2).into_iter().collect(),
+            teams: Vec::new(),
+            entries: vec![RecordEntry::Turn {
+                player_id: 1,
+                action: TurnAction::Draw,
+            }],
+        };
+
+        let error = record.replay().unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::NotTheirTurn {
+                entry: 1,
+                player_id: 1
+            }
+        );
+    }
+
+    #[test]
+    fn replay_fails_if_turn_rejected() {
+        let record = GameRecord {
+            seed: 1,
+            rules: Rules {
+                starting_player: StartingPlayer::Host,
+                ..Default::default()
+            },
+            host_id: Some(0),
+            dealer_id: None,
+            seats: create_players_info(2).into_iter().collect(),
+            teams: Vec::new(),
+            entries: vec![RecordEntry::Turn {
+                player_id: 0,
+                action: TurnAction::Challenge,
+            }],
+        };
+
+        let error = record.replay().unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::TurnRejected {
+                entry: 1,
+                player_id: 0,
+                result: TurnActionResult::NothingToChallenge,
+            }
+        );
+    }
+
+    #[test]
+    fn rejected_turns_are_not_recorded() {
+        let mut uno = Uno::new_with_seed(create_players_info(2), 1).unwrap();
+        uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Challenge);
+
+        assert!(uno.record().entries.is_empty());
+    }
+
+    #[test]
+    fn replay_fails_if_deck_too_large() {
+        let record = GameRecord {
+            seed: 1,
+            rules: Rules {
+                deck_count: Some(u32::MAX),
+                ..Default::default()
+            },
+            host_id: None,
+            dealer_id: None,
+            seats: create_players_info(2).into_iter().collect(),
+            teams: Vec::new(),
+            entries: Vec::new(),
+        };
+
+        let error = record.replay().unwrap_err();
+        assert_eq!(
+            error,
+            RecordError::Setup(UnoError::InvalidDeck(DeckSpecError::TooManyCards))
+        );
+    }
+
+    #[test]
+    fn record_with_deck_too_large_is_written_without_cards() {
+        let record = GameRecord {
+            seed: 1,
+            rules: Rules {
+                deck_count: Some(u32::MAX),
+                ..Default::default()
+            },
+            host_id: None,
+            dealer_id: None,
+            seats: create_players_info(2).into_iter().collect(),
+            teams: Vec::new(),
+            entries: vec![RecordEntry::Turn {
+                player_id: 0,
+                action: TurnAction::Play(PlayAction::Card {
+                    card_id: 0,
+                    chosen_color: None,
+                    uno: false,
+                }),
+            }],
+        };
+
+        let text = record.to_string();
+        assert!(text.ends_with("0 #0\n"));
+    }
+}
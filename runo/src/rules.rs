@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{constants::PLAYERS_PER_DECK, deck_spec::DeckSpec};
+
+/// Everything about how a match is played that may differ from one match to another. Anything
+/// left out when loading rules falls back to the standard game.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    pub deck: DeckSpec,
+    /// How many copies of `deck` are shuffled together. When not set, it depends on how many
+    /// players the match starts with.
+    pub deck_count: Option<u32>,
+    /// Lets the victim of a Wild Draw (+4) challenge it instead of drawing right away. If whoever
+    /// played it held a card of the previous color, they draw four cards instead, otherwise the
+    /// challenger draws six.
+    pub wild_draw_challenge: bool,
+    /// Lets players play an exact duplicate of the colored card on top out of turn. The turn
+    /// order then goes on from whoever jumped in.
+    pub jump_in: bool,
+    /// Playing a 7 lets the player swap hands with another player of their choice, and playing a
+    /// 0 passes every hand on to the next player in turn order.
+    pub seven_zero: bool,
+    /// Drawing keeps drawing cards until one of them can be played, instead of drawing two.
+    pub draw_until_playable: bool,
+    /// Plays the card found with `draw_until_playable` right away, unless it is a wild card,
+    /// which needs a color chosen.
+    pub play_drawn_card: bool,
+    /// Players that hold a card they can play are not allowed to draw.
+    pub must_play_if_able: bool,
+    pub starting_player: StartingPlayer,
+    /// When a team wins a team match, see [`Uno::new_with_teams`](crate::uno::Uno::new_with_teams).
+    pub team_win: TeamWin,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TeamWin {
+    /// As soon as any of its players goes out.
+    #[default]
+    AnyMember,
+    /// Once every one of its players went out.
+    AllMembers,
+}
+
+/// How the player that goes first is picked. Whenever the player it needs is not in the match,
+/// a random player goes first instead.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartingPlayer {
+    #[default]
+    Random,
+    /// Whoever hosts the match.
+    Host,
+    /// The player after the dealer in turn order, where the dealer moves one seat on with every
+    /// match.
+    LeftOfDealer,
+    /// Every player draws a card and the highest number goes first, with action and wild cards
+    /// counting as zero. Tied players keep drawing among themselves.
+    HighestCard,
+}
+
+impl Rules {
+    pub fn deck_count(&self, players_count: usize) -> u32 {
+        self.deck_count
+            .unwrap_or_else(|| players_count.div_ceil(PLAYERS_PER_DECK).max(1) as u32)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deck_count_grows_with_players() {
+        let rules = Rules::default();
+        assert_eq!(rules.deck_count(2), 1);
+        assert_eq!(rules.deck_count(6), 1);
+        assert_eq!(rules.deck_count(7), 2);
+        assert_eq!(rules.deck_count(16), 3);
+
+        let rules = Rules {
+            deck_count: Some(4),
+            ..Default::default()
+        };
+        assert_eq!(rules.deck_count(2), 4);
+    }
+}
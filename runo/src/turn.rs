@@ -1,22 +1,44 @@
-use crate::card::{Card, CardColor};
+use crate::card::{CardColor, CardId};
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum PlayAction {
-    ColoredCard(Card),
-    Wild(CardColor),
-    WildDraw(CardColor),
+    /// Plays the card with the given id from the hand. `chosen_color` is required for wild cards
+    /// and ignored for colored ones. With `uno`, the player says UNO along with their
+    /// second-to-last card, which means nothing for any other card.
+    Card {
+        card_id: CardId,
+        chosen_color: Option<CardColor>,
+        uno: bool,
+    },
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TurnAction {
     Play(PlayAction),
     Draw,
+    /// Calls out every other player that is down to one card without having said UNO. Anyone
+    /// may do this, until the next player acts.
     Callout,
+    /// Says UNO with one card left. Anyone may do this, which gives them until the next player
+    /// acts to say it after their second-to-last card.
     Uno,
+    /// Challenges the Wild Draw (+4) that was just played on the player, see
+    /// [`Rules::wild_draw_challenge`](crate::rules::Rules::wild_draw_challenge).
+    Challenge,
+    /// Swaps hands with the player with the given id after playing a 7, see
+    /// [`Rules::seven_zero`](crate::rules::Rules::seven_zero).
+    SwapHands(u64),
+    /// Chooses the color of the wild card the match started with.
+    ChooseColor(CardColor),
 }
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum TurnActionResult {
     Neutral,
     CardNotInHand,
+    /// The card matches neither the color nor the symbol of the top card.
+    CardNotPlayable,
+    ColorNotChosen,
     Skip,
     Reverse,
     SelfDraw,
@@ -27,4 +49,65 @@ pub enum TurnActionResult {
     CalledOut(Vec<u64>),
     UnoFailed,
     UnoSuccessful,
+    /// The player chose to draw the four cards of a Wild Draw (+4) instead of challenging it.
+    WildDrawAccepted,
+    /// The Wild Draw (+4) was played while holding a card of the previous color, so whoever
+    /// played it draws four cards instead.
+    ChallengeSucceeded,
+    /// The Wild Draw (+4) was played legally, so the challenger draws six cards.
+    ChallengeFailed,
+    NothingToChallenge,
+    /// A Wild Draw (+4) has to be accepted or challenged before anything else can be played.
+    WildDrawPending,
+    /// It is someone else's turn and the action is not a jump-in.
+    NotYourTurn,
+    /// A 7 was played and the player has to choose whom to swap hands with before the turn moves
+    /// on.
+    ChooseHandSwap,
+    /// The player swapped hands with the player with the given id.
+    HandsSwapped(u64),
+    /// A 0 was played and every hand was passed on to the next player.
+    HandsRotated,
+    /// The player has to choose whom to swap hands with before doing anything else.
+    HandSwapPending,
+    /// The player can't swap hands with the chosen player, e.g. with themselves.
+    InvalidSwapTarget,
+    NothingToSwap,
+    /// The player drew this many cards until one could be played, see
+    /// [`Rules::draw_until_playable`](crate::rules::Rules::draw_until_playable). Fewer cards
+    /// were drawn if the deck ran out, in which case the turn passes unless the player can play
+    /// one of their cards.
+    DrewCards(usize),
+    /// The player drew this many cards and played the last one, which had the given result.
+    DrewAndPlayed(usize, Box<TurnActionResult>),
+    /// The player holds a card they can play, so they are not allowed to draw, see
+    /// [`Rules::must_play_if_able`](crate::rules::Rules::must_play_if_able).
+    MustPlayCard,
+    /// The color of the wild card the match started with was chosen, the player goes on with
+    /// their turn.
+    ColorChosen,
+    NoColorToChoose,
+    /// The match is over, so nothing can be played anymore.
+    MatchOver,
+}
+
+impl TurnActionResult {
+    /// Whether the action was refused, in which case the turn did not change.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            Self::CardNotInHand
+                | Self::CardNotPlayable
+                | Self::ColorNotChosen
+                | Self::NothingToChallenge
+                | Self::WildDrawPending
+                | Self::NotYourTurn
+                | Self::HandSwapPending
+                | Self::InvalidSwapTarget
+                | Self::NothingToSwap
+                | Self::MustPlayCard
+                | Self::NoColorToChoose
+                | Self::MatchOver
+        )
+    }
 }
@@ -1,27 +1,96 @@
-use std::{collections::BTreeMap, fmt::Debug};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt::Debug,
+};
 
-use rand::{thread_rng, Rng};
+use rand::{rngs::StdRng, thread_rng, Rng, SeedableRng};
 
-use crate::card::{Card, ColoredCard, PlayedCard};
+use crate::card::{Card, CardColor, CardId, ColoredCard, DeckCard, PlayedCard};
+use crate::constants::{INITIAL_HAND_SIZE, MAX_PLAYERS, MAX_STARTING_DRAW_ROUNDS, MIN_PLAYERS};
 use crate::deck::Deck;
 use crate::error::{Result, UnoError};
 use crate::player::Player;
+use crate::record::{GameRecord, RecordEntry};
+use crate::rules::{Rules, StartingPlayer, TeamWin};
 use crate::turn::{PlayAction, TurnAction, TurnActionResult};
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
 pub struct PlayTurnResult {
     pub turn_action_result: TurnActionResult,
     pub won: bool,
 }
 
+/// What the card the match started with did, as the official rules have it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StartingCardEffect {
+    /// A number card, which does nothing.
+    None,
+    /// The first player was skipped.
+    Skip { player_id: u64 },
+    /// Play goes the other way, starting with the player that would have been last.
+    Reverse,
+    /// The first player drew two cards and was skipped.
+    Draw { player_id: u64 },
+    /// The first player has to choose the color to start with, see [`TurnAction::ChooseColor`].
+    Wild { player_id: u64 },
+}
+
+/// Who went first and how they were picked, see [`Rules::starting_player`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartingPlayerChoice {
+    pub player_id: u64,
+    /// The policy the player was picked by, which is [`StartingPlayer::Random`] whenever the one
+    /// from the rules could not be followed.
+    pub policy: StartingPlayer,
+    /// The cards drawn for [`StartingPlayer::HighestCard`], one round per tie.
+    pub draws: Vec<Vec<(u64, Card)>>,
+}
+
+/// A Wild Draw (+4) that its victim has not accepted or challenged yet.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingWildDraw {
+    pub player_id: u64,
+    pub victim_id: u64,
+    /// The color that was on top before the Wild Draw (+4) was played.
+    pub previous_color: CardColor,
+    /// Whether the player held a card of `previous_color` when they played it, which is not
+    /// allowed.
+    pub held_previous_color: bool,
+}
+
 #[derive(Debug)]
 pub struct Uno {
     deck: Deck,
+    /// The cards played so far, the top card last. Everything but the top card is shuffled back
+    /// into the deck once it runs low.
+    discard_pile: Vec<DeckCard>,
+    /// Continues the randomness of `seed` for reshuffling the discard pile.
+    rng: StdRng,
     players: BTreeMap<u64, Player>,
     winners: BTreeMap<u64, Player>,
     current_turn_player_id_index: usize,
     player_order_reversed: bool,
     last_played_card: PlayedCard,
+    pending_wild_draw: Option<PendingWildDraw>,
+    /// The player that played a 7 and has yet to choose whom to swap hands with.
+    pending_hand_swap: Option<u64>,
+    /// Whether the match started with a wild card whose color has not been chosen yet. Until
+    /// then, the color of `last_played_card` means nothing.
+    starting_color_pending: bool,
+    starting_card_effect: StartingCardEffect,
+    starting_player: StartingPlayerChoice,
+    host_id: Option<u64>,
+    dealer_id: Option<u64>,
+    seed: u64,
+    rules: Rules,
+    /// The seats the match started with.
+    seats: Vec<(u64, String)>,
+    /// The players still in the match, in the order they sit in.
+    seating: Vec<u64>,
+    /// The player ids of every team, empty unless it is a team match.
+    teams: Vec<Vec<u64>>,
+    winning_team: Option<usize>,
+    history: Vec<RecordEntry>,
 }
 
 impl Uno {
@@ -43,108 +112,238 @@ impl Uno {
     }
 
     pub fn new_with_ids(players_info: BTreeMap<u64, String>) -> Result<Self> {
-        if players_info.len() < 2 {
+        Self::new_with_seed(players_info, thread_rng().gen())
+    }
+
+    /// Creates a match whose randomness all comes from `seed`, so that the same seed, players and
+    /// actions always lead to the same match.
+    pub fn new_with_seed(players_info: BTreeMap<u64, String>, seed: u64) -> Result<Self> {
+        Self::new_with_rules(players_info, seed, Rules::default())
+    }
+
+    pub fn new_with_rules(
+        players_info: BTreeMap<u64, String>,
+        seed: u64,
+        rules: Rules,
+    ) -> Result<Self> {
+        let seats = players_info.into_iter().collect();
+        Self::new_with_seats(seats, seed, rules, None, None)
+    }
+
+    /// Creates a match with the players sitting in the order of `seats`, which is the turn order
+    /// as long as nobody plays a Reverse. Knowing who hosts the match and who deals lets
+    /// [`Rules::starting_player`] pick the first player by them.
+    pub fn new_with_seats(
+        seats: Vec<(u64, String)>,
+        seed: u64,
+        rules: Rules,
+        host_id: Option<u64>,
+        dealer_id: Option<u64>,
+    ) -> Result<Self> {
+        if seats.len() < MIN_PLAYERS {
             return Err(UnoError::NotEnoughPlayers);
         }
-        if players_info.len() > 10 {
+        if seats.len() > MAX_PLAYERS {
             return Err(UnoError::TooManyPlayers);
         }
+        let seating = seats
+            .iter()
+            .map(|(player_id, _)| *player_id)
+            .collect::<Vec<_>>();
+        if seating.iter().collect::<BTreeSet<_>>().len() < seating.len() {
+            return Err(UnoError::PlayerAlreadyExists);
+        }
+
+        let deck_count = rules.deck_count(seats.len());
+        rules.deck.validate(deck_count)?;
+        // Everyone gets a hand and one more card is needed to start with
+        let dealt_cards_count = (seats.len() * INITIAL_HAND_SIZE + 1) as u64;
+        if rules.deck.cards_count() * u64::from(deck_count) < dealt_cards_count {
+            return Err(UnoError::NotEnoughCards);
+        }
 
-        let mut deck = Deck::new();
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut deck = Deck::from_spec(&rules.deck, deck_count);
         let mut players = BTreeMap::new();
 
-        deck.shuffle();
+        deck.shuffle(&mut rng);
 
-        for (player_id, player_name) in players_info {
-            let cards = deck.draw_cards(7);
+        for (player_id, player_name) in seats.clone() {
+            let cards = deck.draw_cards(INITIAL_HAND_SIZE);
             let player = Player::new(player_id, player_name, cards);
             players.insert(player_id, player);
         }
 
         let winners = BTreeMap::new();
 
-        let _player_ids = players.keys().copied().collect::<Vec<_>>();
-        // let current_turn_player_id_index = thread_rng().gen_range(0..player_ids.len());
-        let current_turn_player_id_index = 0;
+        let starting_player = Self::choose_starting_player(
+            &seating,
+            rules.starting_player,
+            host_id,
+            dealer_id,
+            &mut deck,
+            &mut rng,
+        );
+        let current_turn_player_id_index = seating
+            .iter()
+            .position(|x| *x == starting_player.player_id)
+            .expect("The starting player is always one of the players.");
 
-        let Card::Colored(color, last_played_card) = deck
-            .draw_colored_card()
-            .expect("There is always at least one card at this point.") else {
-            panic!("Expected to get a colored card.");
+        // Custom decks may have nothing but Wild Draw (+4) cards left after dealing
+        let Some(starting_card) = deck.draw_starting_card(&mut rng) else {
+            return Err(UnoError::NotEnoughCards);
+        };
+        let last_played_card = match starting_card.card.clone() {
+            Card::Colored(color, card) => card.into_played_card(color),
+            // The first player chooses the actual color
+            Card::Wild => PlayedCard::Wild(CardColor::Red),
+            Card::WildDraw => unreachable!("The match never starts with a Wild Draw (+4)."),
         };
 
-        let last_played_card = last_played_card.into_played_card(color);
-
-        Ok(Uno {
+        let mut uno = Uno {
             deck,
+            discard_pile: vec![starting_card],
+            rng,
             players,
             winners,
             current_turn_player_id_index,
             last_played_card,
             player_order_reversed: false,
-        })
+            pending_wild_draw: None,
+            pending_hand_swap: None,
+            starting_color_pending: false,
+            starting_card_effect: StartingCardEffect::None,
+            starting_player,
+            host_id,
+            dealer_id,
+            seed,
+            rules,
+            seats,
+            seating,
+            teams: Vec::new(),
+            winning_team: None,
+            history: Vec::new(),
+        };
+        uno.starting_card_effect = uno.apply_starting_card();
+
+        Ok(uno)
     }
 
-    pub fn play_turn(&mut self, turn_action: TurnAction) -> PlayTurnResult {
-        let current_turn_player_id = self.get_current_turn_player_id();
+    /// Creates a team match, in which partners sit evenly spread around the table, e.g. opposite
+    /// each other with two teams of two. The first seat goes to the first player of the first
+    /// team, the second one to the first player of the second team and so on.
+    ///
+    /// Which team wins is decided by [`Rules::team_win`]. Teams have to be of the same size and
+    /// there have to be at least two of them.
+    pub fn new_with_teams(
+        teams: Vec<Vec<(u64, String)>>,
+        seed: u64,
+        rules: Rules,
+        host_id: Option<u64>,
+        dealer_id: Option<u64>,
+    ) -> Result<Self> {
+        let seats = Self::seat_teams(&teams);
+        let team_ids = teams
+            .iter()
+            .map(|team| team.iter().map(|(player_id, _)| *player_id).collect())
+            .collect();
 
-        let player = self
-            .players
-            .get_mut(&current_turn_player_id)
-            .expect("The player with the current turn must always exist.");
+        let mut uno = Self::new_with_seats(seats, seed, rules, host_id, dealer_id)?;
+        uno.assign_teams(team_ids)?;
+        Ok(uno)
+    }
+
+    /// The order the players of `teams` sit in in a team match, see [`Uno::new_with_teams`].
+    pub fn seat_teams<T: Clone>(teams: &[Vec<T>]) -> Vec<T> {
+        let team_size = teams.iter().map(Vec::len).max().unwrap_or_default();
+        (0..team_size)
+            .flat_map(|index| {
+                teams
+                    .iter()
+                    .filter_map(move |team| team.get(index).cloned())
+            })
+            .collect()
+    }
+
+    /// Splits the players of a match that has not started yet into `teams`.
+    pub(crate) fn assign_teams(&mut self, teams: Vec<Vec<u64>>) -> Result<()> {
+        let team_size = teams.first().map(Vec::len).unwrap_or_default();
+        let team_player_ids = teams.iter().flatten().collect::<BTreeSet<_>>();
+        if teams.len() < 2
+            || team_size == 0
+            || teams.iter().any(|x| x.len() != team_size)
+            || team_player_ids.len() != self.seating.len()
+            || self.seating.iter().any(|x| !team_player_ids.contains(x))
+        {
+            return Err(UnoError::InvalidTeams);
+        }
+
+        self.teams = teams;
+        Ok(())
+    }
+
+    /// Plays `turn_action` for `player_id`, which has to be the player whose turn it is. The
+    /// exceptions are saying UNO and calling someone out, which any player may do at any time, and
+    /// jumping in, see [`Rules::jump_in`], after which the turn order goes on from the player that
+    /// jumped in.
+    pub fn play_turn(&mut self, player_id: u64, turn_action: TurnAction) -> PlayTurnResult {
+        if self.is_over() {
+            return PlayTurnResult {
+                turn_action_result: TurnActionResult::MatchOver,
+                won: false,
+            };
+        }
+
+        let out_of_turn_allowed = matches!(turn_action, TurnAction::Uno | TurnAction::Callout)
+            && self.players.contains_key(&player_id);
+        if player_id != self.get_current_turn_player_id() && !out_of_turn_allowed {
+            if !self.can_jump_in(player_id, &turn_action) {
+                return PlayTurnResult {
+                    turn_action_result: TurnActionResult::NotYourTurn,
+                    won: false,
+                };
+            }
+            self.current_turn_player_id_index = self
+                .get_order_aware_player_ids()
+                .iter()
+                .position(|x| x == &&player_id)
+                .expect("Only players in the match can jump in.");
+        }
+
+        let current_turn_player_id = player_id;
+        // Refused actions change nothing, so they are left out of the record
+        let entry = RecordEntry::Turn {
+            player_id: current_turn_player_id,
+            action: turn_action.clone(),
+        };
+        let closes_callout_window = !out_of_turn_allowed;
+        let callable_player_ids = self.get_callable_player_ids(&player_id);
 
         let turn_action_result = match turn_action {
-            TurnAction::Play(play_action) => match play_action {
-                PlayAction::ColoredCard(card) => {
-                    if let Some(hand_card_index) = player.card_index(&card) {
-                        player.remove_card(hand_card_index);
-                        match card {
-                            Card::Colored(color, card) => {
-                                let result = match card {
-                                    ColoredCard::Skip => {
-                                        self.move_turn_n_players_ahead(2);
-                                        TurnActionResult::Skip
-                                    }
-                                    ColoredCard::Reverse => {
-                                        self.perform_reverse();
-                                        TurnActionResult::Reverse
-                                    }
-                                    ColoredCard::Draw => {
-                                        self.draw_cards_to_player(
-                                            &self.get_next_turn_player_id(),
-                                            2,
-                                        );
-                                        self.move_turn_n_players_ahead(1);
-                                        TurnActionResult::Draw
-                                    }
-                                    ColoredCard::Number(_) => {
-                                        self.move_turn_n_players_ahead(1);
-                                        TurnActionResult::Neutral
-                                    }
-                                };
-                                self.last_played_card = card.into_played_card(color);
-                                result
-                            }
-                            _ => unreachable!(),
-                        }
-                    } else {
-                        TurnActionResult::CardNotInHand
-                    }
-                }
-                PlayAction::Wild(color) => {
-                    self.last_played_card = PlayedCard::Wild(color);
-                    self.move_turn_n_players_ahead(1);
-                    TurnActionResult::Wild
-                }
-                PlayAction::WildDraw(color) => {
-                    self.last_played_card = PlayedCard::WildDraw(color);
-                    self.draw_cards_to_player(&self.get_next_turn_player_id(), 4);
-                    self.move_turn_n_players_ahead(1);
-                    TurnActionResult::WildDraw
-                }
-            },
+            TurnAction::ChooseColor(color) => self.choose_starting_color(color),
+            TurnAction::Play(_)
+            | TurnAction::Draw
+            | TurnAction::Challenge
+            | TurnAction::SwapHands(_)
+                if self.starting_color_pending =>
+            {
+                TurnActionResult::ColorNotChosen
+            }
+            TurnAction::Play(_) | TurnAction::Draw | TurnAction::Challenge
+                if self.pending_hand_swap.is_some() =>
+            {
+                TurnActionResult::HandSwapPending
+            }
+            TurnAction::Play(_) if self.pending_wild_draw.is_some() => {
+                TurnActionResult::WildDrawPending
+            }
+            TurnAction::Play(PlayAction::Card {
+                card_id,
+                chosen_color,
+                uno,
+            }) => self.play_card(&current_turn_player_id, card_id, chosen_color, uno),
             TurnAction::Callout => {
-                let called_out_player_ids = self.perform_callout();
+                let called_out_player_ids = self.perform_callout(&current_turn_player_id);
                 if called_out_player_ids.is_empty() {
                     TurnActionResult::CalloutFailed
                 } else {
@@ -152,18 +351,29 @@ impl Uno {
                 }
             }
             TurnAction::Uno => {
-                if self.perform_uno() {
+                if self.perform_uno(&current_turn_player_id) {
                     TurnActionResult::UnoSuccessful
                 } else {
                     TurnActionResult::UnoFailed
                 }
             }
-            TurnAction::Draw => {
-                self.draw_cards_to_player(&current_turn_player_id, 2);
-                TurnActionResult::SelfDraw
+            TurnAction::Draw if self.pending_wild_draw.is_some() => {
+                self.accept_wild_draw();
+                TurnActionResult::WildDrawAccepted
             }
+            TurnAction::Draw => self.draw_for_turn(&current_turn_player_id),
+            TurnAction::Challenge => self.challenge_wild_draw(),
+            TurnAction::SwapHands(target_id) => self.swap_hands(target_id),
         };
 
+        if !turn_action_result.is_rejected() {
+            self.history.push(entry);
+        }
+
+        if closes_callout_window && !turn_action_result.is_rejected() {
+            self.close_callout_window(callable_player_ids);
+        }
+
         let player = self
             .players
             .get(&current_turn_player_id)
@@ -171,12 +381,37 @@ impl Uno {
 
         let won = player.cards_count() == 0;
         if won {
+            // The turn has already moved on, it only comes back to the winner if everyone else
+            // was skipped
+            let new_turn_player_id = match self.get_current_turn_player_id() {
+                x if x == current_turn_player_id => self.get_next_turn_player_id(),
+                x => x,
+            };
+            self.seating.retain(|x| *x != current_turn_player_id);
             self.winners.insert(
                 current_turn_player_id,
                 self.players
                     .remove(&current_turn_player_id)
                     .expect("The player just won."),
             );
+
+            if let Some(team) = self.get_team(&current_turn_player_id) {
+                let team_won = match self.rules.team_win {
+                    TeamWin::AnyMember => true,
+                    TeamWin::AllMembers => self.teams[team]
+                        .iter()
+                        .all(|x| !self.players.contains_key(x)),
+                };
+                if team_won {
+                    self.winning_team = Some(team);
+                }
+            }
+
+            self.current_turn_player_id_index = self
+                .get_order_aware_player_ids()
+                .iter()
+                .position(|x| x == &&new_turn_player_id)
+                .unwrap_or(0);
         }
 
         PlayTurnResult {
@@ -185,8 +420,124 @@ impl Uno {
         }
     }
 
+    /// Adds a player to an ongoing match and deals them a fresh hand.
+    ///
+    /// The new player takes the last seat, right before the first one. The current turn stays with
+    /// the same player it was with before the new player was added.
+    pub fn add_player(&mut self, player_id: u64, player_name: String) -> Result<()> {
+        if !self.teams.is_empty() {
+            return Err(UnoError::TeamMatchStarted);
+        }
+        if self.players.contains_key(&player_id) || self.winners.contains_key(&player_id) {
+            return Err(UnoError::PlayerAlreadyExists);
+        }
+        if self.players.len() >= MAX_PLAYERS {
+            return Err(UnoError::TooManyPlayers);
+        }
+        // Everything but the top card of the discard pile can be shuffled back into the deck
+        if self.deck.cards_count() + self.discard_pile.len() - 1 < INITIAL_HAND_SIZE {
+            return Err(UnoError::NotEnoughCards);
+        }
+
+        let current_turn_player_id = self.get_current_turn_player_id();
+
+        let cards = self.draw_from_deck(INITIAL_HAND_SIZE);
+        let player = Player::new(player_id, player_name.clone(), cards);
+        self.players.insert(player_id, player);
+        self.seating.push(player_id);
+        self.history.push(RecordEntry::Join {
+            player_id,
+            player_name,
+        });
+
+        self.current_turn_player_id_index = self
+            .get_order_aware_player_ids()
+            .iter()
+            .position(|x| x == &&current_turn_player_id)
+            .expect("The current player must still exist, only a new player was added.");
+
+        Ok(())
+    }
+
+    /// Removes a player from an ongoing match and puts their hand at the bottom of the deck.
+    ///
+    /// If it was the removed player's turn, the turn passes on to the next player.
+    pub fn remove_player(&mut self, player_id: &u64) -> Result<Player> {
+        if !self.players.contains_key(player_id) {
+            return Err(UnoError::PlayerNotFound);
+        }
+
+        let current_turn_player_id = self.get_current_turn_player_id();
+        let new_turn_player_id = if &current_turn_player_id == player_id {
+            self.get_next_turn_player_id()
+        } else {
+            current_turn_player_id
+        };
+
+        // Nobody is left to draw for a Wild Draw (+4) that was played by or on the player
+        if self
+            .pending_wild_draw
+            .as_ref()
+            .is_some_and(|x| x.player_id == *player_id || x.victim_id == *player_id)
+        {
+            self.pending_wild_draw = None;
+        }
+        if self.pending_hand_swap == Some(*player_id) {
+            self.pending_hand_swap = None;
+        }
+
+        let mut player = self
+            .players
+            .remove(player_id)
+            .expect("The player was just checked to exist.");
+        self.seating.retain(|x| x != player_id);
+        self.deck.0.append(&mut player.hand);
+        self.history.push(RecordEntry::Leave {
+            player_id: *player_id,
+        });
+
+        self.current_turn_player_id_index = self
+            .get_order_aware_player_ids()
+            .iter()
+            .position(|x| x == &&new_turn_player_id)
+            .unwrap_or(0);
+
+        Ok(player)
+    }
+
+    /// Passes the turn to the next player without the current player doing anything, e.g. when
+    /// they took too long.
+    ///
+    /// A skipped victim of a Wild Draw (+4) draws its cards as if they accepted it, and a skipped
+    /// player that played a 7 keeps their hand.
+    pub fn skip_turn(&mut self) {
+        let callable_player_ids = self.get_callable_player_ids(&self.get_current_turn_player_id());
+        self.close_callout_window(callable_player_ids);
+        self.history.push(RecordEntry::SkipTurn);
+        self.pending_hand_swap = None;
+        if self.pending_wild_draw.is_some() {
+            self.accept_wild_draw();
+        } else {
+            self.move_turn_n_players_ahead(1);
+        }
+    }
+
+    /// Everything needed to rebuild this match from scratch, see [`GameRecord`].
+    pub fn record(&self) -> GameRecord {
+        GameRecord {
+            seed: self.seed,
+            rules: self.rules.clone(),
+            host_id: self.host_id,
+            dealer_id: self.dealer_id,
+            seats: self.seats.clone(),
+            teams: self.teams.clone(),
+            entries: self.history.clone(),
+        }
+    }
+
+    /// The players still in the match, in seat order.
     pub fn get_player_ids(&self) -> Vec<u64> {
-        self.players.keys().copied().collect()
+        self.seating.clone()
     }
 
     pub fn get_player(&self, player_id: &u64) -> Option<&Player> {
@@ -221,8 +572,488 @@ impl Uno {
         &self.last_played_card
     }
 
+    /// How many turns it takes until it is the player's turn, 0 if it is their turn right now.
+    pub fn get_turn_distance(&self, player_id: &u64) -> Option<usize> {
+        let player_ids = self.get_order_aware_player_ids();
+        let index = player_ids.iter().position(|x| x == &player_id)?;
+        Some((index + player_ids.len() - self.current_turn_player_id_index) % player_ids.len())
+    }
+
+    pub fn get_pending_wild_draw(&self) -> Option<&PendingWildDraw> {
+        self.pending_wild_draw.as_ref()
+    }
+
+    /// The player that played a 7 and has to choose whom to swap hands with.
+    pub fn get_pending_hand_swap(&self) -> Option<u64> {
+        self.pending_hand_swap
+    }
+
+    /// Whether the match started with a wild card and the current player has yet to choose its
+    /// color.
+    pub fn is_starting_color_pending(&self) -> bool {
+        self.starting_color_pending
+    }
+
+    pub fn get_starting_card_effect(&self) -> &StartingCardEffect {
+        &self.starting_card_effect
+    }
+
+    pub fn get_starting_player(&self) -> &StartingPlayerChoice {
+        &self.starting_player
+    }
+
+    pub fn get_rules(&self) -> &Rules {
+        &self.rules
+    }
+
+    /// The player ids of every team in a team match, see [`Uno::new_with_teams`].
+    pub fn get_teams(&self) -> &[Vec<u64>] {
+        &self.teams
+    }
+
+    /// The index of the team the player is in, whether they are still in the match or not.
+    pub fn get_team(&self, player_id: &u64) -> Option<usize> {
+        self.teams.iter().position(|x| x.contains(player_id))
+    }
+
+    pub fn get_winning_team(&self) -> Option<usize> {
+        self.winning_team
+    }
+
+    /// The points of the cards the other teams' players still hold, which is what the team scores
+    /// when it wins.
+    pub fn get_team_score(&self, team: usize) -> u32 {
+        self.players
+            .values()
+            .filter(|x| self.get_team(&x.id) != Some(team))
+            .flat_map(|x| &x.hand)
+            .map(|x| x.card.points())
+            .sum()
+    }
+
+    /// Whether the match is over, which is once a team won a team match or once less than two
+    /// players or teams are left to play.
+    pub fn is_over(&self) -> bool {
+        if self.teams.is_empty() {
+            return self.players.len() < 2;
+        }
+        let teams_left = self
+            .teams
+            .iter()
+            .filter(|team| team.iter().any(|x| self.players.contains_key(x)))
+            .count();
+        self.winning_team.is_some() || teams_left < 2
+    }
+
+    fn play_card(
+        &mut self,
+        player_id: &u64,
+        card_id: CardId,
+        chosen_color: Option<CardColor>,
+        uno: bool,
+    ) -> TurnActionResult {
+        let player = self
+            .players
+            .get_mut(player_id)
+            .expect("The player playing a card must always exist.");
+
+        let Some(hand_card_index) = player.card_index(card_id) else {
+            return TurnActionResult::CardNotInHand;
+        };
+        if !player.hand[hand_card_index]
+            .card
+            .can_be_played_on(&self.last_played_card)
+        {
+            return TurnActionResult::CardNotPlayable;
+        }
+
+        let played_card = match (player.hand[hand_card_index].card.clone(), chosen_color) {
+            (Card::Colored(color, card), _) => card.into_played_card(color),
+            (Card::Wild, Some(color)) => PlayedCard::Wild(color),
+            (Card::WildDraw, Some(color)) => PlayedCard::WildDraw(color),
+            (Card::Wild | Card::WildDraw, None) => return TurnActionResult::ColorNotChosen,
+        };
+
+        let deck_card = player.hand[hand_card_index].clone();
+        player.remove_card(hand_card_index);
+        self.discard_pile.push(deck_card);
+        let player = self
+            .players
+            .get_mut(player_id)
+            .expect("The player playing a card must always exist.");
+        // Saying UNO with any other card is not worth a penalty
+        if uno && player.cards_count() == 1 {
+            player.uno();
+        }
+
+        let previous_color = self.last_played_card.color();
+        let held_previous_color = player
+            .hand
+            .iter()
+            .any(|x| matches!(x.card, Card::Colored(color, _) if color == previous_color));
+
+        let won = player.cards_count() == 0;
+        let result = match &played_card {
+            PlayedCard::Colored(_, ColoredCard::Skip) => {
+                self.move_turn_n_players_ahead(2);
+                TurnActionResult::Skip
+            }
+            // With two players, going the other way would just be the other player's turn
+            PlayedCard::Colored(_, ColoredCard::Reverse) if self.players.len() == 2 => {
+                self.move_turn_n_players_ahead(2);
+                TurnActionResult::Skip
+            }
+            PlayedCard::Colored(_, ColoredCard::Reverse) => {
+                self.perform_reverse();
+                self.move_turn_n_players_ahead(1);
+                TurnActionResult::Reverse
+            }
+            // Whoever has to draw also loses their turn
+            PlayedCard::Colored(_, ColoredCard::Draw) => {
+                self.draw_cards_to_player(&self.get_next_turn_player_id(), 2);
+                self.move_turn_n_players_ahead(2);
+                TurnActionResult::Draw
+            }
+            // Whoever plays their last card wins, so there is nothing left to swap or rotate
+            PlayedCard::Colored(_, ColoredCard::Number(7)) if self.rules.seven_zero && !won => {
+                self.pending_hand_swap = Some(*player_id);
+                TurnActionResult::ChooseHandSwap
+            }
+            PlayedCard::Colored(_, ColoredCard::Number(0)) if self.rules.seven_zero && !won => {
+                self.rotate_hands();
+                self.move_turn_n_players_ahead(1);
+                TurnActionResult::HandsRotated
+            }
+            PlayedCard::Colored(_, ColoredCard::Number(_)) => {
+                self.move_turn_n_players_ahead(1);
+                TurnActionResult::Neutral
+            }
+            PlayedCard::Wild(_) => {
+                self.move_turn_n_players_ahead(1);
+                TurnActionResult::Wild
+            }
+            PlayedCard::WildDraw(_) if self.rules.wild_draw_challenge => {
+                // The victim gets their turn to either accept or challenge it
+                self.pending_wild_draw = Some(PendingWildDraw {
+                    player_id: *player_id,
+                    victim_id: self.get_next_turn_player_id(),
+                    previous_color,
+                    held_previous_color,
+                });
+                self.move_turn_n_players_ahead(1);
+                TurnActionResult::WildDraw
+            }
+            PlayedCard::WildDraw(_) => {
+                self.draw_cards_to_player(&self.get_next_turn_player_id(), 4);
+                self.move_turn_n_players_ahead(2);
+                TurnActionResult::WildDraw
+            }
+        };
+
+        self.last_played_card = played_card;
+        result
+    }
+
+    /// Whether the player may play out of turn, which is only the case when jumping in with an
+    /// exact duplicate of the colored card on top.
+    fn can_jump_in(&self, player_id: u64, turn_action: &TurnAction) -> bool {
+        let TurnAction::Play(PlayAction::Card { card_id, .. }) = turn_action else {
+            return false;
+        };
+        if !self.rules.jump_in
+            || self.pending_wild_draw.is_some()
+            || self.pending_hand_swap.is_some()
+        {
+            return false;
+        }
+        let Some(deck_card) = self
+            .get_player(&player_id)
+            .and_then(|x| x.get_card(*card_id))
+        else {
+            return false;
+        };
+
+        match (&deck_card.card, &self.last_played_card) {
+            (Card::Colored(color, card), PlayedCard::Colored(top_color, top_card)) => {
+                color == top_color && card == top_card
+            }
+            _ => false,
+        }
+    }
+
+    /// The victim of the pending Wild Draw (+4) draws its four cards and loses their turn.
+    fn accept_wild_draw(&mut self) {
+        if let Some(pending) = self.pending_wild_draw.take() {
+            self.draw_cards_to_player(&pending.victim_id, 4);
+            self.move_turn_n_players_ahead(1);
+        }
+    }
+
+    fn challenge_wild_draw(&mut self) -> TurnActionResult {
+        let Some(pending) = self.pending_wild_draw.take() else {
+            return TurnActionResult::NothingToChallenge;
+        };
+
+        if pending.held_previous_color {
+            // The victim goes on with their turn as if nothing was played on them. Whoever played
+            // the card can't have won with it, since they still held another card.
+            self.draw_cards_to_player(&pending.player_id, 4);
+            TurnActionResult::ChallengeSucceeded
+        } else {
+            self.draw_cards_to_player(&pending.victim_id, 6);
+            self.move_turn_n_players_ahead(1);
+            TurnActionResult::ChallengeFailed
+        }
+    }
+
+    /// Picks who goes first by `policy`, or randomly if the player it needs is missing.
+    fn choose_starting_player(
+        player_ids: &[u64],
+        policy: StartingPlayer,
+        host_id: Option<u64>,
+        dealer_id: Option<u64>,
+        deck: &mut Deck,
+        rng: &mut impl Rng,
+    ) -> StartingPlayerChoice {
+        let position = |player_id: Option<u64>| {
+            player_id.and_then(|player_id| player_ids.iter().position(|x| *x == player_id))
+        };
+        let index = match policy {
+            StartingPlayer::Random => None,
+            StartingPlayer::Host => position(host_id),
+            StartingPlayer::LeftOfDealer => {
+                position(dealer_id).map(|index| (index + 1) % player_ids.len())
+            }
+            StartingPlayer::HighestCard => {
+                return Self::draw_for_starting_player(player_ids, deck, rng)
+            }
+        };
+        let (index, policy) = match index {
+            Some(index) => (index, policy),
+            None => (rng.gen_range(0..player_ids.len()), StartingPlayer::Random),
+        };
+
+        StartingPlayerChoice {
+            player_id: player_ids[index],
+            policy,
+            draws: Vec::new(),
+        }
+    }
+
+    /// Every player draws a card until one of them drew the highest, see
+    /// [`StartingPlayer::HighestCard`]. The drawn cards are shuffled back into the deck.
+    fn draw_for_starting_player(
+        player_ids: &[u64],
+        deck: &mut Deck,
+        rng: &mut impl Rng,
+    ) -> StartingPlayerChoice {
+        let value = |card: &Card| match card {
+            Card::Colored(_, ColoredCard::Number(number)) => *number,
+            _ => 0,
+        };
+
+        let mut contenders = player_ids.to_vec();
+        let mut draws = Vec::new();
+        while contenders.len() > 1
+            && draws.len() < MAX_STARTING_DRAW_ROUNDS
+            && deck.cards_count() >= contenders.len()
+        {
+            let cards = deck.draw_cards(contenders.len());
+            let round = contenders
+                .iter()
+                .zip(&cards)
+                .map(|(player_id, deck_card)| (*player_id, deck_card.card.clone()))
+                .collect::<Vec<_>>();
+            let highest = round.iter().map(|(_, card)| value(card)).max();
+            contenders = round
+                .iter()
+                .filter(|(_, card)| Some(value(card)) == highest)
+                .map(|(player_id, _)| *player_id)
+                .collect();
+
+            deck.0.extend(cards);
+            deck.shuffle(rng);
+            draws.push(round);
+        }
+
+        // A tie that could not be broken is settled randomly
+        let player_id = match contenders[..] {
+            [player_id] => player_id,
+            _ => contenders[rng.gen_range(0..contenders.len())],
+        };
+
+        StartingPlayerChoice {
+            player_id,
+            policy: StartingPlayer::HighestCard,
+            draws,
+        }
+    }
+
+    fn apply_starting_card(&mut self) -> StartingCardEffect {
+        let player_id = self.get_current_turn_player_id();
+
+        match self.last_played_card {
+            PlayedCard::Colored(_, ColoredCard::Number(_)) => StartingCardEffect::None,
+            PlayedCard::Colored(_, ColoredCard::Skip) => {
+                self.move_turn_n_players_ahead(1);
+                StartingCardEffect::Skip { player_id }
+            }
+            PlayedCard::Colored(_, ColoredCard::Reverse) => {
+                self.perform_reverse();
+                self.move_turn_n_players_ahead(1);
+                StartingCardEffect::Reverse
+            }
+            PlayedCard::Colored(_, ColoredCard::Draw) => {
+                self.draw_cards_to_player(&player_id, 2);
+                self.move_turn_n_players_ahead(1);
+                StartingCardEffect::Draw { player_id }
+            }
+            PlayedCard::Wild(_) => {
+                self.starting_color_pending = true;
+                StartingCardEffect::Wild { player_id }
+            }
+            PlayedCard::WildDraw(_) => {
+                unreachable!("The match never starts with a Wild Draw (+4).")
+            }
+        }
+    }
+
+    /// Sets the color of the wild card the match started with. The player goes on with their turn
+    /// afterwards.
+    fn choose_starting_color(&mut self, color: CardColor) -> TurnActionResult {
+        if !self.starting_color_pending {
+            return TurnActionResult::NoColorToChoose;
+        }
+        self.starting_color_pending = false;
+        self.last_played_card = PlayedCard::Wild(color);
+        TurnActionResult::ColorChosen
+    }
+
+    fn draw_for_turn(&mut self, player_id: &u64) -> TurnActionResult {
+        let player = self
+            .get_player(player_id)
+            .expect("The player with the current turn must always exist.");
+        let can_play = player
+            .hand
+            .iter()
+            .any(|x| x.card.can_be_played_on(&self.last_played_card));
+        if self.rules.must_play_if_able && can_play {
+            return TurnActionResult::MustPlayCard;
+        }
+
+        if !self.rules.draw_until_playable {
+            self.draw_cards_to_player(player_id, 2);
+            self.pass_if_stuck(player_id);
+            return TurnActionResult::SelfDraw;
+        }
+
+        let mut drawn_count = 0;
+        let mut playable_card = None;
+        while playable_card.is_none() {
+            let Some(deck_card) = self.draw_from_deck(1).pop() else {
+                break;
+            };
+            drawn_count += 1;
+            if deck_card.card.can_be_played_on(&self.last_played_card) {
+                playable_card = Some(deck_card.clone());
+            }
+            self.get_player_mut(player_id)
+                .expect("Player has disappeared...")
+                .add_card(deck_card);
+        }
+
+        match playable_card {
+            Some(DeckCard {
+                id,
+                card: Card::Colored(_, _),
+            }) if self.rules.play_drawn_card => {
+                let result = self.play_card(player_id, id, None, false);
+                TurnActionResult::DrewAndPlayed(drawn_count, Box::new(result))
+            }
+            _ => {
+                self.pass_if_stuck(player_id);
+                TurnActionResult::DrewCards(drawn_count)
+            }
+        }
+    }
+
+    /// Passes the turn if the player can neither play any of their cards nor draw another one,
+    /// as drawing again would change nothing.
+    fn pass_if_stuck(&mut self, player_id: &u64) {
+        let can_draw = self.deck.cards_count() > 0 || self.discard_pile.len() > 1;
+        let can_play = self
+            .get_player(player_id)
+            .expect("The player with the current turn must always exist.")
+            .hand
+            .iter()
+            .any(|x| x.card.can_be_played_on(&self.last_played_card));
+        if !can_draw && !can_play {
+            self.move_turn_n_players_ahead(1);
+        }
+    }
+
+    fn swap_hands(&mut self, target_id: u64) -> TurnActionResult {
+        let Some(player_id) = self.pending_hand_swap else {
+            return TurnActionResult::NothingToSwap;
+        };
+        if target_id == player_id || !self.players.contains_key(&target_id) {
+            return TurnActionResult::InvalidSwapTarget;
+        }
+        self.pending_hand_swap = None;
+
+        let hand = self.take_hand(&player_id);
+        let target_hand = self.take_hand(&target_id);
+        self.give_hand(&player_id, target_hand);
+        self.give_hand(&target_id, hand);
+
+        self.move_turn_n_players_ahead(1);
+        TurnActionResult::HandsSwapped(target_id)
+    }
+
+    /// Every player passes their hand on to the next player in turn order.
+    fn rotate_hands(&mut self) {
+        let player_ids: Vec<u64> = self
+            .get_order_aware_player_ids()
+            .into_iter()
+            .copied()
+            .collect();
+        let mut hands: Vec<_> = player_ids.iter().map(|x| self.take_hand(x)).collect();
+        hands.rotate_right(1);
+
+        for (player_id, hand) in player_ids.iter().zip(hands) {
+            self.give_hand(player_id, hand);
+        }
+    }
+
+    fn take_hand(&mut self, player_id: &u64) -> Vec<DeckCard> {
+        let player = self
+            .get_player_mut(player_id)
+            .expect("Player has disappeared...");
+        std::mem::take(&mut player.hand)
+    }
+
+    fn give_hand(&mut self, player_id: &u64, hand: Vec<DeckCard>) {
+        let player = self
+            .get_player_mut(player_id)
+            .expect("Player has disappeared...");
+        player.hand = hand;
+        player.uno_performed = false;
+    }
+
+    /// Draws `count` cards, shuffling the discard pile back into the deck if there are not
+    /// enough left. Fewer cards are drawn if there are not enough in the whole match.
+    fn draw_from_deck(&mut self, count: usize) -> Vec<DeckCard> {
+        if self.deck.cards_count() < count && self.discard_pile.len() > 1 {
+            let top_card = self.discard_pile.pop().expect("The pile was just checked.");
+            self.deck.0.append(&mut self.discard_pile);
+            self.discard_pile.push(top_card);
+            self.deck.shuffle(&mut self.rng);
+        }
+        self.deck.draw_cards(count)
+    }
+
     fn draw_cards_to_player(&mut self, player_id: &u64, count: usize) {
-        let cards = self.deck.draw_cards(count);
+        let cards = self.draw_from_deck(count);
 
         let player = self
             .get_player_mut(player_id)
@@ -243,22 +1074,32 @@ impl Uno {
             .expect("This must exist since only the order has been reversed, nothing was removed.");
     }
 
-    fn perform_callout(&mut self) -> Vec<u64> {
-        let mut called_out_player_ids = vec![];
-        let current_turn_player_id = self.get_current_turn_player_id();
-
-        for player in self.players.values() {
-            if player.id == current_turn_player_id {
-                continue;
-            }
+    /// The players other than `player_id` that can be called out right now.
+    fn get_callable_player_ids(&self, player_id: &u64) -> Vec<u64> {
+        self.players
+            .values()
+            .filter(|x| x.id != *player_id && x.can_be_called_out())
+            .map(|x| x.id)
+            .collect()
+    }
 
-            if player.cards_count() == 1 && !player.uno_performed {
-                called_out_player_ids.push(player.id);
+    /// Whoever was down to one card before the next player acted has been lucky and can't be
+    /// called out anymore.
+    fn close_callout_window(&mut self, player_ids: Vec<u64>) {
+        for player_id in player_ids {
+            if let Some(player) = self.players.get_mut(&player_id) {
+                if player.can_be_called_out() {
+                    player.callout_window_passed = true;
+                }
             }
         }
+    }
+
+    fn perform_callout(&mut self, player_id: &u64) -> Vec<u64> {
+        let called_out_player_ids = self.get_callable_player_ids(player_id);
 
         if called_out_player_ids.is_empty() {
-            self.draw_cards_to_player(&current_turn_player_id, 2);
+            self.draw_cards_to_player(player_id, 2);
         } else {
             for called_out_player_id in &called_out_player_ids {
                 self.draw_cards_to_player(called_out_player_id, 2);
@@ -268,54 +1109,43 @@ impl Uno {
         called_out_player_ids
     }
 
-    fn perform_uno(&mut self) -> bool {
-        let current_turn_player_id = self.get_current_turn_player_id();
-        let current_player = self
-            .get_player_mut(&current_turn_player_id)
-            .expect("Current player must always exist.");
+    fn perform_uno(&mut self, player_id: &u64) -> bool {
+        let player = self
+            .get_player_mut(player_id)
+            .expect("Only players in the match can say UNO.");
 
-        if current_player.cards_count() == 1 {
-            current_player.uno();
+        if player.cards_count() == 1 {
+            player.uno();
             true
         } else {
-            self.draw_cards_to_player(&current_turn_player_id, 2);
+            self.draw_cards_to_player(player_id, 2);
             false
         }
     }
 
     fn get_order_aware_player_ids(&self) -> Vec<&u64> {
-        let keys_iter = self.players.keys();
+        let seats_iter = self.seating.iter();
         let player_ids: Vec<&u64> = if self.player_order_reversed {
-            keys_iter.rev().collect()
+            seats_iter.rev().collect()
         } else {
-            keys_iter.collect()
+            seats_iter.collect()
         };
         player_ids
     }
 
     fn get_nth_turn_player_id(&self, n: usize) -> u64 {
         let player_ids = self.get_order_aware_player_ids();
-        return **player_ids
+        **player_ids
             .iter()
             .cycle()
             .nth(self.current_turn_player_id_index + n)
-            .expect("Cycle always returns something...right?");
+            .expect("Cycle always returns something...right?")
     }
 
     fn move_turn_n_players_ahead(&mut self, n: usize) {
-        for _ in 0..n {
-            self.current_turn_player_id_index = if self.player_order_reversed {
-                if self.current_turn_player_id_index == 0 {
-                    self.players.len() - 1
-                } else {
-                    self.current_turn_player_id_index - 1
-                }
-            } else if self.current_turn_player_id_index == self.players.len() - 1 {
-                0
-            } else {
-                self.current_turn_player_id_index + 1
-            };
-        }
+        // The index is into the order aware player ids, so ahead is always the next index
+        self.current_turn_player_id_index =
+            (self.current_turn_player_id_index + n) % self.players.len();
     }
 
     // fn get_random_player_id(players: &BTreeMap<u64, Player>) -> u64 {
@@ -330,8 +1160,13 @@ impl Uno {
 
 #[cfg(test)]
 mod tests {
+    use strum::IntoEnumIterator;
+
     use super::*;
 
+    /// Matches with this seed start with a number card, so the first seat has the first turn.
+    const SEED: u64 = 0;
+
     fn create_player_names(count: usize) -> Vec<String> {
         let mut player_names = Vec::new();
         for i in 0..count {
@@ -362,13 +1197,32 @@ mod tests {
 
     #[test]
     fn return_err_if_too_many_players() {
-        let error = Uno::new(create_player_names(11)).unwrap_err();
+        let error = Uno::new(create_player_names(MAX_PLAYERS + 1)).unwrap_err();
         assert!(matches!(error, UnoError::TooManyPlayers));
     }
 
+    #[test]
+    fn large_matches_play_with_more_decks() {
+        let uno = Uno::new_with_seed(create_players_info(16), SEED).unwrap();
+
+        assert_eq!(uno.players.len(), 16);
+        assert_eq!(uno.deck.cards_count(), 3 * 108 - 16 * 7 - 1);
+    }
+
+    #[test]
+    fn return_err_if_not_enough_cards() {
+        let rules = Rules {
+            deck_count: Some(1),
+            ..Default::default()
+        };
+
+        let error = Uno::new_with_rules(create_players_info(16), 0, rules).unwrap_err();
+        assert!(matches!(error, UnoError::NotEnoughCards));
+    }
+
     #[test]
     fn all_players_start_with_7_cards() {
-        let uno = Uno::new(create_player_names(4)).unwrap();
+        let uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
         for (_id, player) in uno.players {
             assert_eq!(player.cards_count(), 7);
         }
@@ -376,7 +1230,7 @@ mod tests {
 
     #[test]
     fn next_player_turn_works_when_first_player() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         // Set current turn to first player, random by default
         uno.current_turn_player_id_index = 0;
@@ -388,7 +1242,7 @@ mod tests {
 
     #[test]
     fn next_player_turn_works_when_last_player() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         // Set current turn to last player, random by default
         uno.current_turn_player_id_index = 3;
@@ -400,7 +1254,7 @@ mod tests {
 
     #[test]
     fn next_player_turn_works_when_other_player() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         // Set current turn to some other player, random by default
         uno.current_turn_player_id_index = 1;
@@ -412,7 +1266,7 @@ mod tests {
 
     #[test]
     fn skip_player_turn_works() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         // Set current turn to first player, random by default
         uno.current_turn_player_id_index = 0;
@@ -424,7 +1278,7 @@ mod tests {
 
     #[test]
     fn perform_reverse_works() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         // Set current turn to first player, random by default
         uno.current_turn_player_id_index = 0;
@@ -436,9 +1290,9 @@ mod tests {
 
     #[test]
     fn perform_uno_does_not_work_unless_one_card_left() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
-        let uno_successful = uno.perform_uno();
+        let uno_successful = uno.perform_uno(&uno.get_current_turn_player_id());
 
         let player = uno
             .get_player(&uno.get_current_turn_player_id())
@@ -451,21 +1305,163 @@ mod tests {
 
     #[test]
     fn perform_uno_works_if_one_card_left() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
         let player = uno
             .get_player_mut(&uno.get_current_turn_player_id())
             .expect("Player must exist.");
 
         player.hand.truncate(1);
 
-        assert!(uno.perform_uno())
+        assert!(uno.perform_uno(&uno.get_current_turn_player_id()))
+    }
+
+    #[test]
+    fn add_player_deals_a_fresh_hand() {
+        let mut uno = Uno::new_with_seed(create_players_info(3), SEED).unwrap();
+
+        uno.add_player(10, "Player 11".to_string()).unwrap();
+
+        let player = uno.get_player(&10).expect("Added player must exist.");
+        assert_eq!(player.cards_count(), 7);
+        assert_eq!(uno.deck.cards_count(), 108 - 4 * 7 - 1);
+    }
+
+    #[test]
+    fn add_player_keeps_current_turn() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+
+        uno.current_turn_player_id_index = 2;
+        uno.add_player(10, "Player 11".to_string()).unwrap();
+        assert_eq!(uno.get_current_turn_player_id(), 2);
+
+        uno.perform_reverse();
+        uno.add_player(1_000, "Player 1001".to_string()).unwrap();
+        assert_eq!(uno.get_current_turn_player_id(), 2);
+        assert_eq!(uno.get_next_turn_player_id(), 1);
+    }
+
+    #[test]
+    fn add_player_fails_if_already_in_match() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+
+        let error = uno.add_player(1, "Player 2".to_string()).unwrap_err();
+        assert!(matches!(error, UnoError::PlayerAlreadyExists));
+    }
+
+    #[test]
+    fn add_player_fails_if_too_many_players() {
+        let mut uno = Uno::new_with_seed(create_players_info(MAX_PLAYERS), SEED).unwrap();
+
+        let error = uno
+            .add_player(1_000, "Player 1001".to_string())
+            .unwrap_err();
+        assert!(matches!(error, UnoError::TooManyPlayers));
+    }
+
+    #[test]
+    fn add_player_fails_if_not_enough_cards() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+        uno.deck.0.truncate(INITIAL_HAND_SIZE - 1);
+
+        let error = uno.add_player(10, "Player 11".to_string()).unwrap_err();
+        assert!(matches!(error, UnoError::NotEnoughCards));
+    }
+
+    #[test]
+    fn drawing_reshuffles_discard_pile() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+        let top_card = uno.discard_pile.pop().unwrap();
+        uno.discard_pile.append(&mut uno.deck.0);
+        uno.discard_pile.push(top_card.clone());
+        let discarded_count = uno.discard_pile.len() - 1;
+
+        let player_id = uno.get_current_turn_player_id();
+        uno.play_turn(player_id, TurnAction::Draw);
+
+        assert_eq!(uno.get_player(&player_id).unwrap().cards_count(), 9);
+        assert_eq!(uno.deck.cards_count(), discarded_count - 2);
+        assert_eq!(uno.discard_pile, vec![top_card]);
+    }
+
+    #[test]
+    fn drawing_without_cards_left_draws_nothing() {
+        for draw_until_playable in [false, true] {
+            let rules = Rules {
+                draw_until_playable,
+                ..Default::default()
+            };
+            let mut uno = Uno::new_with_rules(create_players_info(4), SEED, rules).unwrap();
+            uno.deck.0.clear();
+
+            let player_id = uno.get_current_turn_player_id();
+            uno.play_turn(player_id, TurnAction::Draw);
+            assert_eq!(uno.get_player(&player_id).unwrap().cards_count(), 7);
+        }
+    }
+
+    #[test]
+    fn turn_passes_if_nothing_can_be_drawn() {
+        for draw_until_playable in [false, true] {
+            let rules = Rules {
+                draw_until_playable,
+                ..Default::default()
+            };
+            let mut uno = Uno::new_with_rules(create_players_info(4), SEED, rules).unwrap();
+            uno.deck.0.clear();
+            let PlayedCard::Colored(top_color, _) = uno.last_played_card else {
+                unreachable!("Matches with seed 0 start with a number card.");
+            };
+            let other_color = CardColor::iter().find(|x| *x != top_color).unwrap();
+
+            let player_id = uno.get_current_turn_player_id();
+            for deck_card in &mut uno.get_player_mut(&player_id).unwrap().hand {
+                deck_card.card = Card::Colored(other_color, ColoredCard::Skip);
+            }
+            uno.play_turn(player_id, TurnAction::Draw);
+
+            assert_eq!(uno.get_player(&player_id).unwrap().cards_count(), 7);
+            assert_ne!(uno.get_current_turn_player_id(), player_id);
+        }
+    }
+
+    #[test]
+    fn remove_player_returns_hand_to_deck() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+        let deck_cards_count = uno.deck.cards_count();
+
+        let player = uno.remove_player(&2).unwrap();
+
+        assert_eq!(player.cards_count(), 0);
+        assert_eq!(uno.deck.cards_count(), deck_cards_count + 7);
+        assert_eq!(uno.get_player_ids(), vec![0, 1, 3]);
+    }
+
+    #[test]
+    fn remove_player_passes_turn_if_current_player() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+
+        uno.current_turn_player_id_index = 1;
+        uno.remove_player(&1).unwrap();
+        assert_eq!(uno.get_current_turn_player_id(), 2);
+
+        uno.remove_player(&0).unwrap();
+        assert_eq!(uno.get_current_turn_player_id(), 2);
+        assert_eq!(uno.get_next_turn_player_id(), 3);
+    }
+
+    #[test]
+    fn remove_player_fails_if_not_in_match() {
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
+
+        let error = uno.remove_player(&10).unwrap_err();
+        assert!(matches!(error, UnoError::PlayerNotFound));
     }
 
     #[test]
     fn perform_callout_does_not_work_if_no_players_can_be_called_out() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
-        let called_out_player_ids = uno.perform_callout();
+        let called_out_player_ids = uno.perform_callout(&uno.get_current_turn_player_id());
 
         let player = uno
             .get_player(&uno.get_current_turn_player_id())
@@ -483,14 +1479,17 @@ mod tests {
 
     #[test]
     fn perform_callout_works_if_players_can_be_called_out() {
-        let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+        let mut uno = Uno::new_with_seed(create_players_info(4), SEED).unwrap();
 
         let next_player = uno
             .get_player_mut(&uno.get_next_turn_player_id())
             .expect("Next player must exist.");
         next_player.hand.truncate(1);
 
-        assert_eq!(uno.perform_callout().len(), 1);
+        assert_eq!(
+            uno.perform_callout(&uno.get_current_turn_player_id()).len(),
+            1
+        );
 
         let player = uno
             .get_player(&uno.get_current_turn_player_id())
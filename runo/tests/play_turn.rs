@@ -1,18 +1,18 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
+
+use strum::IntoEnumIterator;
 
 use runo::{
     card::{Card, CardColor, ColoredCard, PlayedCard},
+    deck_spec::{ColorSpec, DeckSpec},
+    error::UnoError,
+    rules::{Rules, StartingPlayer, TeamWin},
     turn::{PlayAction, TurnAction, TurnActionResult},
-    uno::{PlayTurnResult, Uno},
+    uno::{PlayTurnResult, StartingCardEffect, Uno},
 };
 
-fn create_player_names(count: usize) -> Vec<String> {
-    let mut player_names = Vec::new();
-    for i in 0..count {
-        player_names.push(format!("Player {}", i + 1));
-    }
-    player_names
-}
+/// Matches with this seed start with a Red 9, so the first player has the first turn.
+const SEED: u64 = 0;
 
 fn create_players_info(count: usize) -> BTreeMap<u64, String> {
     let mut players_info = BTreeMap::new();
@@ -22,20 +22,187 @@ fn create_players_info(count: usize) -> BTreeMap<u64, String> {
     players_info
 }
 
+/// Sets up the matches of these tests. Unless told otherwise, it starts a match with `SEED` where
+/// the players sit in the order of their ids and the first one hosts and goes first.
+struct MatchBuilder {
+    seats: Vec<(u64, String)>,
+    teams: Option<Vec<Vec<u64>>>,
+    rules: Rules,
+    starting_player: StartingPlayer,
+    seed: u64,
+    host_id: Option<u64>,
+    dealer_id: Option<u64>,
+    hands: Vec<(u64, Vec<Card>)>,
+}
+
+impl MatchBuilder {
+    fn new(players_count: usize) -> Self {
+        let seats = create_players_info(players_count)
+            .into_iter()
+            .collect::<Vec<_>>();
+        let host_id = seats.first().map(|(player_id, _)| *player_id);
+        Self {
+            seats,
+            teams: None,
+            rules: Rules::default(),
+            starting_player: StartingPlayer::Host,
+            seed: SEED,
+            host_id,
+            dealer_id: None,
+            hands: Vec::new(),
+        }
+    }
+
+    /// Seats these players instead, with the first one hosting.
+    fn seats(mut self, seats: &[(u64, &str)]) -> Self {
+        self.seats = seats
+            .iter()
+            .map(|(player_id, name)| (*player_id, name.to_string()))
+            .collect();
+        self.host_id = seats.first().map(|(player_id, _)| *player_id);
+        self
+    }
+
+    /// Starts a team match of the seated players instead.
+    fn teams(mut self, teams: &[&[u64]]) -> Self {
+        self.teams = Some(teams.iter().map(|x| x.to_vec()).collect());
+        self
+    }
+
+    /// Plays by `rules`, except for who goes first, see [`MatchBuilder::starting_player`].
+    fn rules(mut self, rules: Rules) -> Self {
+        self.rules = rules;
+        self
+    }
+
+    fn starting_player(mut self, starting_player: StartingPlayer) -> Self {
+        self.starting_player = starting_player;
+        self
+    }
+
+    fn seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    fn host(mut self, host_id: Option<u64>) -> Self {
+        self.host_id = host_id;
+        self
+    }
+
+    fn dealer(mut self, dealer_id: Option<u64>) -> Self {
+        self.dealer_id = dealer_id;
+        self
+    }
+
+    /// Starts the match with `card` on top by making the deck of nothing but copies of it. A
+    /// deck always needs a colored card, so a Wild one comes with a single Red 1.
+    fn top_card(mut self, card: Card) -> Self {
+        let no_cards = ColorSpec {
+            numbers: [0; 10],
+            skip: 0,
+            reverse: 0,
+            draw: 0,
+        };
+        let mut red = no_cards.clone();
+        let mut wild = 0;
+        match card {
+            Card::Colored(CardColor::Red, card) => match card {
+                ColoredCard::Number(number) => red.numbers[number as usize] = 40,
+                ColoredCard::Skip => red.skip = 40,
+                ColoredCard::Reverse => red.reverse = 40,
+                ColoredCard::Draw => red.draw = 40,
+            },
+            Card::Wild => {
+                red.numbers[1] = 1;
+                wild = 40;
+            }
+            _ => unimplemented!("Matches of these tests only start with red or Wild cards."),
+        }
+
+        self.rules.deck = DeckSpec {
+            red,
+            green: no_cards.clone(),
+            blue: no_cards.clone(),
+            yellow: no_cards,
+            wild,
+            wild_draw: 0,
+        };
+        self
+    }
+
+    /// Replaces the hand `player_id` was dealt with `cards`, which take over the ids of the
+    /// dealt cards in order.
+    fn hand(mut self, player_id: u64, cards: Vec<Card>) -> Self {
+        self.hands.push((player_id, cards));
+        self
+    }
+
+    fn try_build(self) -> Result<Uno, UnoError> {
+        let rules = Rules {
+            starting_player: self.starting_player,
+            ..self.rules
+        };
+        let mut uno = match self.teams {
+            Some(teams) => {
+                let teams = teams
+                    .iter()
+                    .map(|team| {
+                        team.iter()
+                            .map(|player_id| {
+                                self.seats
+                                    .iter()
+                                    .find(|(x, _)| x == player_id)
+                                    .expect("Team members must be seated.")
+                                    .clone()
+                            })
+                            .collect()
+                    })
+                    .collect();
+                Uno::new_with_teams(teams, self.seed, rules, self.host_id, self.dealer_id)?
+            }
+            None => {
+                Uno::new_with_seats(self.seats, self.seed, rules, self.host_id, self.dealer_id)?
+            }
+        };
+
+        for (player_id, cards) in self.hands {
+            let player = uno.get_player_mut(&player_id).expect("Player must exist.");
+            assert!(cards.len() <= player.hand.len(), "Hands can only shrink.");
+            player.hand.truncate(cards.len());
+            for (deck_card, card) in player.hand.iter_mut().zip(cards) {
+                deck_card.card = card;
+            }
+        }
+
+        Ok(uno)
+    }
+
+    fn build(self) -> Uno {
+        self.try_build().unwrap()
+    }
+}
+
 #[test]
 fn play_turn_works_if_card_in_hand() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
         .expect("Current player must exist.");
 
     // We add a "Red 1" card to the player so that we can test for it below
-    player.hand[0] = Card::Colored(CardColor::Red, ColoredCard::Number(1));
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(CardColor::Red, ColoredCard::Number(1)),
-    )));
+    player.hand[0].card = Card::Colored(CardColor::Red, ColoredCard::Number(1));
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -53,29 +220,25 @@ fn play_turn_works_if_card_in_hand() {
 
 #[test]
 fn play_turn_fails_if_card_not_in_hand() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
-    let player = uno
-        .get_player_mut(&uno.get_current_turn_player_id())
-        .expect("Current player must exist.");
-
-    // Change every "Red 1" card to "Green 1" so that we can later test that a "Red 1" card
-    // does not exist.
-    for card in &mut player.hand {
-        if let Card::Colored(color, colored_card) = card {
-            if color == &CardColor::Red {
-                if let ColoredCard::Number(1) = colored_card {
-                    *card = Card::Colored(CardColor::Green, ColoredCard::Number(1));
-                }
-            }
-        }
-    }
+    // A card from another player's hand is definitely not in the current player's hand
+    let card_id = uno
+        .get_player(&uno.get_next_turn_player_id())
+        .expect("Next player must exist.")
+        .hand[0]
+        .id;
 
     let last_played_card_before = uno.get_last_played_card().clone();
 
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(CardColor::Red, ColoredCard::Number(1)),
-    )));
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -90,9 +253,79 @@ fn play_turn_fails_if_card_not_in_hand() {
     assert_eq!(last_played_card, &last_played_card_before);
 }
 
+/// Gives the current player a card and lets them play it.
+fn play_given_card(uno: &mut Uno, card: Card, chosen_color: Option<CardColor>) -> PlayTurnResult {
+    let player_id = uno.get_current_turn_player_id();
+    let player = uno
+        .get_player_mut(&player_id)
+        .expect("Current player must exist.");
+    player.hand[0].card = card;
+    let card_id = player.hand[0].id;
+
+    uno.play_turn(
+        player_id,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color,
+            uno: false,
+        }),
+    )
+}
+
+#[test]
+fn play_turn_fails_if_card_not_playable() {
+    let mut uno = MatchBuilder::new(4).build();
+    let last_played_card_before = uno.get_last_played_card().clone();
+    let other_color = CardColor::iter()
+        .find(|x| *x != last_played_card_before.color())
+        .unwrap();
+
+    // Matches with `SEED` start with a number card, so a Skip of another color matches nothing
+    let turn_action_result = play_given_card(
+        &mut uno,
+        Card::Colored(other_color, ColoredCard::Skip),
+        None,
+    );
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::CardNotPlayable
+    );
+    assert_eq!(uno.get_last_played_card(), &last_played_card_before);
+    assert_eq!(uno.get_current_turn_player_id(), 0);
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 7);
+}
+
+#[test]
+fn only_chosen_color_can_be_played_on_wild_draw() {
+    let mut uno = MatchBuilder::new(4).build();
+    play_given_card(&mut uno, Card::WildDraw, Some(CardColor::Red));
+    assert_eq!(uno.get_current_turn_player_id(), 2);
+
+    let turn_action_result = play_given_card(
+        &mut uno,
+        Card::Colored(CardColor::Blue, ColoredCard::Skip),
+        None,
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::CardNotPlayable
+    );
+
+    let turn_action_result = play_given_card(
+        &mut uno,
+        Card::Colored(CardColor::Red, ColoredCard::Skip),
+        None,
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::Skip
+    );
+}
+
 #[test]
 fn play_turn_skips_player_properly() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
     let current_turn_player_id = uno.get_current_turn_player_id();
     let expected_next_player_id = if current_turn_player_id == 2 {
         0
@@ -107,11 +340,17 @@ fn play_turn_skips_player_properly() {
         .expect("Current player must exist.");
 
     // We change the first card of the player to "Red Skip" so we can test for it below
-    player.hand[0] = Card::Colored(CardColor::Red, ColoredCard::Skip);
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(CardColor::Red, ColoredCard::Skip),
-    )));
+    player.hand[0].card = Card::Colored(CardColor::Red, ColoredCard::Skip);
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     assert_eq!(uno.get_current_turn_player_id(), expected_next_player_id);
 
@@ -131,7 +370,7 @@ fn play_turn_skips_player_properly() {
 
 #[test]
 fn play_turn_performs_reverse_properly() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
     let current_turn_player_id = uno.get_current_turn_player_id();
     let prev_player_id = if current_turn_player_id == 0 {
@@ -140,16 +379,24 @@ fn play_turn_performs_reverse_properly() {
         current_turn_player_id - 1
     };
 
+    let color = uno.get_last_played_card().color();
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
         .expect("Current player must exist.");
 
-    // We change the first card of the player to "Green Reverse" so we can test for it below
-    player.hand[0] = Card::Colored(CardColor::Green, ColoredCard::Reverse);
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(CardColor::Green, ColoredCard::Reverse),
-    )));
+    // We change the first card of the player to a Reverse of the top color so we can test for it
+    // below
+    player.hand[0].card = Card::Colored(color, ColoredCard::Reverse);
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -161,32 +408,37 @@ fn play_turn_performs_reverse_properly() {
 
     assert!(matches!(
         uno.get_last_played_card(),
-        PlayedCard::Colored(CardColor::Green, ColoredCard::Reverse)
+        PlayedCard::Colored(x, ColoredCard::Reverse) if *x == color
     ));
 
-    assert_eq!(uno.get_next_turn_player_id(), prev_player_id);
+    assert_eq!(uno.get_current_turn_player_id(), prev_player_id);
 }
 
 #[test]
 fn play_turn_performs_draw_properly() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
     let current_turn_player_id = uno.get_current_turn_player_id();
-    let expected_next_player_id = if current_turn_player_id == 3 {
-        0
-    } else {
-        current_turn_player_id + 1
-    };
+    let victim_id = (current_turn_player_id + 1) % 4;
+    let expected_next_player_id = (current_turn_player_id + 2) % 4;
 
+    let color = uno.get_last_played_card().color();
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
         .expect("Current player must exist.");
 
-    // We change the first card of the player to "Green Draw" so we can test for it below
-    player.hand[0] = Card::Colored(CardColor::Green, ColoredCard::Draw);
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(CardColor::Green, ColoredCard::Draw),
-    )));
+    // We change the first card of the player to a Draw of the top color so we can test for it
+    // below
+    player.hand[0].card = Card::Colored(color, ColoredCard::Draw);
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -198,23 +450,22 @@ fn play_turn_performs_draw_properly() {
 
     assert!(matches!(
         uno.get_last_played_card(),
-        PlayedCard::Colored(CardColor::Green, ColoredCard::Draw)
+        PlayedCard::Colored(x, ColoredCard::Draw) if *x == color
     ));
 
-    let next_player_id = uno.get_current_turn_player_id();
-
-    assert_eq!(next_player_id, expected_next_player_id);
+    // The victim draws and loses their turn
+    assert_eq!(uno.get_current_turn_player_id(), expected_next_player_id);
 
-    let next_player = uno
-        .get_player_mut(&next_player_id)
-        .expect("The next player has disappeared.");
+    let victim = uno
+        .get_player_mut(&victim_id)
+        .expect("The victim has disappeared.");
 
-    assert_eq!(next_player.cards_count(), 9);
+    assert_eq!(victim.cards_count(), 9);
 }
 
 #[test]
 fn play_turn_performs_wild_properly() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
     let current_turn_player_id = uno.get_current_turn_player_id();
     let expected_next_player_id = if current_turn_player_id == 3 {
         0
@@ -227,9 +478,17 @@ fn play_turn_performs_wild_properly() {
         .expect("Current player must exist.");
 
     // We change the first card of the player to "Wild" so we can test for it below
-    player.hand[0] = Card::Wild;
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::Wild(CardColor::Red)));
+    player.hand[0].card = Card::Wild;
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: Some(CardColor::Red),
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -247,27 +506,117 @@ fn play_turn_performs_wild_properly() {
     let next_player_id = uno.get_current_turn_player_id();
 
     assert_eq!(next_player_id, expected_next_player_id);
+
+    let next_player = uno
+        .get_player_mut(&next_player_id)
+        .expect("The next player has disappeared.");
+
+    assert_eq!(next_player.cards_count(), 7);
+}
+
+#[test]
+fn play_turn_fails_if_wild_color_not_chosen() {
+    let mut uno = MatchBuilder::new(4).build();
+    let current_turn_player_id = uno.get_current_turn_player_id();
+
+    let player = uno
+        .get_player_mut(&current_turn_player_id)
+        .expect("Current player must exist.");
+
+    // We change the first card of the player to "Wild" so we can test for it below
+    player.hand[0].card = Card::Wild;
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    assert_eq!(
+        turn_action_result,
+        PlayTurnResult {
+            turn_action_result: TurnActionResult::ColorNotChosen,
+            won: false
+        }
+    );
+
+    assert_eq!(uno.get_current_turn_player_id(), current_turn_player_id);
+
+    let player = uno
+        .get_player(&current_turn_player_id)
+        .expect("Current player must exist.");
+
+    assert_eq!(player.cards_count(), 7);
+}
+
+#[test]
+fn play_turn_plays_exact_card_among_duplicates() {
+    let mut uno = MatchBuilder::new(4).build();
+    let current_turn_player_id = uno.get_current_turn_player_id();
+    let color = uno.get_last_played_card().color();
+
+    let player = uno
+        .get_player_mut(&current_turn_player_id)
+        .expect("Current player must exist.");
+
+    // We make the first two cards of the player identical so we can tell them apart below
+    player.hand[0].card = Card::Colored(color, ColoredCard::Number(4));
+    player.hand[1].card = Card::Colored(color, ColoredCard::Number(4));
+    let kept_card_id = player.hand[0].id;
+    let played_card_id = player.hand[1].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id: played_card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    assert_eq!(
+        turn_action_result,
+        PlayTurnResult {
+            turn_action_result: TurnActionResult::Neutral,
+            won: false
+        }
+    );
+
+    let player = uno
+        .get_player(&current_turn_player_id)
+        .expect("Current player must exist.");
+
+    assert!(player.get_card(kept_card_id).is_some());
+    assert!(player.get_card(played_card_id).is_none());
 }
 
 #[test]
 fn play_turn_performs_wild_draw_properly() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
     let current_turn_player_id = uno.get_current_turn_player_id();
-    let expected_next_player_id = if current_turn_player_id == 3 {
-        0
-    } else {
-        current_turn_player_id + 1
-    };
+    let victim_id = (current_turn_player_id + 1) % 4;
+    let expected_next_player_id = (current_turn_player_id + 2) % 4;
 
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
         .expect("Current player must exist.");
 
     // We change the first card of the player to "Green Draw" so we can test for it below
-    player.hand[0] = Card::WildDraw;
-
-    let turn_action_result =
-        uno.play_turn(TurnAction::Play(PlayAction::WildDraw(CardColor::Yellow)));
+    player.hand[0].card = Card::WildDraw;
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: Some(CardColor::Yellow),
+            uno: false,
+        }),
+    );
 
     assert_eq!(
         turn_action_result,
@@ -282,20 +631,19 @@ fn play_turn_performs_wild_draw_properly() {
         PlayedCard::WildDraw(CardColor::Yellow)
     ));
 
-    let next_player_id = uno.get_current_turn_player_id();
-
-    assert_eq!(next_player_id, expected_next_player_id);
+    // The victim draws and loses their turn
+    assert_eq!(uno.get_current_turn_player_id(), expected_next_player_id);
 
-    let next_player = uno
-        .get_player_mut(&next_player_id)
-        .expect("The next player has disappeared.");
+    let victim = uno
+        .get_player_mut(&victim_id)
+        .expect("The victim has disappeared.");
 
-    assert_eq!(next_player.cards_count(), 11);
+    assert_eq!(victim.cards_count(), 11);
 }
 
 #[test]
 fn turn_uno_works_if_only_one_card() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
@@ -303,7 +651,7 @@ fn turn_uno_works_if_only_one_card() {
 
     player.hand.truncate(1);
 
-    let turn_action_result = uno.play_turn(TurnAction::Uno);
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Uno);
 
     assert_eq!(
         turn_action_result,
@@ -316,7 +664,7 @@ fn turn_uno_works_if_only_one_card() {
 
 #[test]
 fn turn_uno_does_not_work_if_more_than_one_card() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
     let player = uno
         .get_player_mut(&uno.get_current_turn_player_id())
@@ -324,7 +672,7 @@ fn turn_uno_does_not_work_if_more_than_one_card() {
 
     player.hand.truncate(4);
 
-    let turn_action_result = uno.play_turn(TurnAction::Uno);
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Uno);
 
     assert_eq!(
         turn_action_result,
@@ -337,20 +685,26 @@ fn turn_uno_does_not_work_if_more_than_one_card() {
 
 #[test]
 fn turn_callout_works_if_players_eligible() {
-    let mut uno = Uno::new_with_ids(create_players_info(4)).unwrap();
-
-    let next_player = uno.get_player_mut(&1).expect("Next player must exist.");
-    next_player.hand.truncate(1);
+    let mut uno = MatchBuilder::new(4).build();
 
-    let next_next_player = uno.get_player_mut(&2).expect("Next player must exist.");
-    next_next_player.hand.truncate(1);
+    // The starting card may have passed the first turn on to someone else
+    let current_player_id = uno.get_current_turn_player_id();
+    let other_player_ids = (0..4)
+        .filter(|x| *x != current_player_id)
+        .take(2)
+        .collect::<Vec<_>>();
+
+    for player_id in &other_player_ids {
+        let player = uno.get_player_mut(player_id).expect("Player must exist.");
+        player.hand.truncate(1);
+    }
 
-    let turn_action_result = uno.play_turn(TurnAction::Callout);
+    let turn_action_result = uno.play_turn(current_player_id, TurnAction::Callout);
 
     assert_eq!(
         turn_action_result,
         PlayTurnResult {
-            turn_action_result: TurnActionResult::CalledOut(vec![1, 2]),
+            turn_action_result: TurnActionResult::CalledOut(other_player_ids),
             won: false
         }
     );
@@ -358,9 +712,9 @@ fn turn_callout_works_if_players_eligible() {
 
 #[test]
 fn turn_callout_does_not_work_if_no_players_eligible() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
-    let turn_action_result = uno.play_turn(TurnAction::Callout);
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Callout);
 
     assert_eq!(
         turn_action_result,
@@ -373,26 +727,27 @@ fn turn_callout_does_not_work_if_no_players_eligible() {
 
 #[test]
 fn turn_winning_works_properly() {
-    let mut uno = Uno::new(create_player_names(4)).unwrap();
+    let mut uno = MatchBuilder::new(4).build();
 
-    let color = match uno.get_last_played_card() {
-        PlayedCard::Colored(color, _) => color,
-        PlayedCard::Wild(color) => color,
-        PlayedCard::WildDraw(color) => color,
-    }
-    .clone();
+    let color = uno.get_last_played_card().color();
 
     let current_player_id = uno.get_current_turn_player_id();
 
     let player = uno
         .get_player_mut(&current_player_id)
         .expect("Current player must exist.");
-    player.hand.truncate(0);
-    player.add_card(Card::Colored(color.clone(), ColoredCard::Skip));
-
-    let turn_action_result = uno.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-        Card::Colored(color, ColoredCard::Skip),
-    )));
+    player.hand.truncate(1);
+    player.hand[0].card = Card::Colored(color, ColoredCard::Skip);
+    let card_id = player.hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
 
     let winner = uno
         .get_winner(&current_player_id)
@@ -407,3 +762,888 @@ fn turn_winning_works_properly() {
         }
     );
 }
+
+#[test]
+fn turn_passes_to_next_seat_after_winning() {
+    let mut uno = MatchBuilder::new(4).build();
+
+    assert!(go_out(&mut uno).won);
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+    assert_eq!(uno.get_next_turn_player_id(), 2);
+
+    uno.skip_turn();
+    assert_eq!(uno.get_current_turn_player_id(), 2);
+}
+
+/// Starts a match on a Red 9 where the Wild Draw (+4) can be challenged and lets player 0 play
+/// one. The rest of their hand is made of Wild cards, plus a red card if `held_previous_color`.
+fn play_challengeable_wild_draw(held_previous_color: bool) -> Uno {
+    let mut hand = vec![Card::Wild; 7];
+    hand[0] = Card::WildDraw;
+    if held_previous_color {
+        hand[1] = Card::Colored(CardColor::Red, ColoredCard::Number(3));
+    }
+    let mut uno = MatchBuilder::new(4)
+        .rules(Rules {
+            wild_draw_challenge: true,
+            ..Default::default()
+        })
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Number(9)))
+        .hand(0, hand)
+        .build();
+    let card_id = uno.get_player(&0).unwrap().hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        0,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: Some(CardColor::Blue),
+            uno: false,
+        }),
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::WildDraw
+    );
+
+    uno
+}
+
+#[test]
+fn challengeable_wild_draw_waits_for_victim() {
+    let mut uno = play_challengeable_wild_draw(false);
+
+    let pending = uno
+        .get_pending_wild_draw()
+        .expect("Wild Draw must be pending.");
+    assert_eq!(pending.player_id, 0);
+    assert_eq!(pending.victim_id, 1);
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+    assert_eq!(uno.get_player(&1).unwrap().cards_count(), 7);
+
+    let card_id = uno.get_player(&1).unwrap().hand[0].id;
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: Some(CardColor::Red),
+            uno: false,
+        }),
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::WildDrawPending
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+#[test]
+fn draw_accepts_pending_wild_draw() {
+    let mut uno = play_challengeable_wild_draw(true);
+
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Draw);
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::WildDrawAccepted
+    );
+    assert_eq!(uno.get_player(&1).unwrap().cards_count(), 11);
+    assert_eq!(uno.get_current_turn_player_id(), 2);
+    assert!(uno.get_pending_wild_draw().is_none());
+}
+
+#[test]
+fn challenge_succeeds_if_previous_color_was_held() {
+    let mut uno = play_challengeable_wild_draw(true);
+
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Challenge);
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::ChallengeSucceeded
+    );
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 10);
+    assert_eq!(uno.get_player(&1).unwrap().cards_count(), 7);
+    // The challenger gets to play their turn
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+#[test]
+fn challenge_fails_if_wild_draw_was_legal() {
+    let mut uno = play_challengeable_wild_draw(false);
+
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Challenge);
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::ChallengeFailed
+    );
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 6);
+    assert_eq!(uno.get_player(&1).unwrap().cards_count(), 13);
+    assert_eq!(uno.get_current_turn_player_id(), 2);
+}
+
+#[test]
+fn challenge_fails_without_pending_wild_draw() {
+    let mut uno = MatchBuilder::new(4).build();
+
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Challenge);
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NothingToChallenge
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 0);
+}
+
+/// Player 0 plays a Red 5 on a Red 9 while player 2 holds another one.
+fn play_jump_in_setup(jump_in: bool) -> (Uno, u32) {
+    let red_five = Card::Colored(CardColor::Red, ColoredCard::Number(5));
+    let hand = vec![red_five, Card::Wild, Card::Wild];
+    let mut uno = MatchBuilder::new(4)
+        .rules(Rules {
+            jump_in,
+            ..Default::default()
+        })
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Number(9)))
+        .hand(0, hand.clone())
+        .hand(2, hand)
+        .build();
+    let card_id = uno.get_player(&0).unwrap().hand[0].id;
+    let jump_in_card_id = uno.get_player(&2).unwrap().hand[0].id;
+
+    let turn_action_result = uno.play_turn(
+        0,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::Neutral
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+
+    (uno, jump_in_card_id)
+}
+
+#[test]
+fn jump_in_takes_over_turn_order() {
+    let (mut uno, card_id) = play_jump_in_setup(true);
+    assert_eq!(uno.get_turn_distance(&2), Some(1));
+
+    let turn_action_result = uno.play_turn(
+        2,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::Neutral
+    );
+    assert!(uno.get_player(&2).unwrap().get_card(card_id).is_none());
+    assert_eq!(uno.get_current_turn_player_id(), 3);
+}
+
+#[test]
+fn jump_in_rejected_if_disabled() {
+    let (mut uno, card_id) = play_jump_in_setup(false);
+
+    let turn_action_result = uno.play_turn(
+        2,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NotYourTurn
+    );
+    assert!(uno.get_player(&2).unwrap().get_card(card_id).is_some());
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+#[test]
+fn jump_in_rejected_if_card_differs() {
+    let (mut uno, card_id) = play_jump_in_setup(true);
+    let player = uno.get_player_mut(&2).expect("Player 2 must exist.");
+    player.hand[0].card = Card::Colored(CardColor::Blue, ColoredCard::Number(5));
+
+    let turn_action_result = uno.play_turn(
+        2,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NotYourTurn
+    );
+
+    let turn_action_result = uno.play_turn(2, TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NotYourTurn
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+fn hand_card_ids(uno: &Uno, player_id: u64) -> Vec<u32> {
+    let player = uno.get_player(&player_id).expect("Player must exist.");
+    player.hand.iter().map(|x| x.id).collect()
+}
+
+/// Player 0 plays a Red `number` on a Red 9 under the Seven-Zero rule. Also returns every
+/// player's hand right after the card left it.
+fn play_seven_zero(number: u8) -> (Uno, Vec<Vec<u32>>, PlayTurnResult) {
+    let red_card = Card::Colored(CardColor::Red, ColoredCard::Number(number));
+    let mut uno = MatchBuilder::new(4)
+        .rules(Rules {
+            seven_zero: true,
+            ..Default::default()
+        })
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Number(9)))
+        .hand(0, vec![red_card, Card::Wild, Card::Wild])
+        .build();
+    let card_id = uno.get_player(&0).unwrap().hand[0].id;
+
+    let mut hands: Vec<_> = (0..4).map(|id| hand_card_ids(&uno, id)).collect();
+    hands[0].retain(|x| *x != card_id);
+
+    let turn_action_result = uno.play_turn(
+        0,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    (uno, hands, turn_action_result)
+}
+
+#[test]
+fn seven_waits_for_hand_swap_target() {
+    let (mut uno, hands, turn_action_result) = play_seven_zero(7);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::ChooseHandSwap
+    );
+    assert_eq!(uno.get_pending_hand_swap(), Some(0));
+    assert_eq!(uno.get_current_turn_player_id(), 0);
+
+    for turn_action in [TurnAction::Draw, TurnAction::Challenge] {
+        let turn_action_result = uno.play_turn(0, turn_action);
+        assert_eq!(
+            turn_action_result.turn_action_result,
+            TurnActionResult::HandSwapPending
+        );
+    }
+    for target_id in [0, 4] {
+        let turn_action_result = uno.play_turn(0, TurnAction::SwapHands(target_id));
+        assert_eq!(
+            turn_action_result.turn_action_result,
+            TurnActionResult::InvalidSwapTarget
+        );
+    }
+
+    let turn_action_result = uno.play_turn(0, TurnAction::SwapHands(2));
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::HandsSwapped(2)
+    );
+    assert_eq!(uno.get_pending_hand_swap(), None);
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+
+    assert_eq!(hand_card_ids(&uno, 0), hands[2]);
+    assert_eq!(hand_card_ids(&uno, 1), hands[1]);
+    assert_eq!(hand_card_ids(&uno, 2), hands[0]);
+}
+
+#[test]
+fn swap_hands_rejected_without_seven() {
+    let mut uno = MatchBuilder::new(4).build();
+
+    let turn_action_result = uno.play_turn(
+        uno.get_current_turn_player_id(),
+        TurnAction::SwapHands(uno.get_next_turn_player_id()),
+    );
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NothingToSwap
+    );
+}
+
+#[test]
+fn zero_rotates_hands() {
+    let (uno, hands, turn_action_result) = play_seven_zero(0);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::HandsRotated
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+
+    for player_id in 0..4 {
+        let previous_player_id = (player_id + 3) % 4;
+        assert_eq!(
+            hand_card_ids(&uno, player_id),
+            hands[previous_player_id as usize]
+        );
+    }
+}
+
+/// A hand of which nothing can be played on the Red 9 that matches with `SEED` start with.
+fn unplayable_hand() -> Vec<Card> {
+    vec![Card::Colored(CardColor::Green, ColoredCard::Skip); 7]
+}
+
+#[test]
+fn must_play_if_able_rejects_draw() {
+    let rules = Rules {
+        must_play_if_able: true,
+        ..Default::default()
+    };
+    let mut uno = MatchBuilder::new(4)
+        .rules(rules.clone())
+        .hand(0, unplayable_hand())
+        .build();
+    let player_id = uno.get_current_turn_player_id();
+
+    let turn_action_result = uno.play_turn(player_id, TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::SelfDraw
+    );
+
+    let mut hand = unplayable_hand();
+    hand[3] = Card::Wild;
+    let mut uno = MatchBuilder::new(4).rules(rules).hand(0, hand).build();
+
+    let turn_action_result = uno.play_turn(player_id, TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::MustPlayCard
+    );
+    assert_eq!(uno.get_player(&player_id).unwrap().cards_count(), 7);
+}
+
+#[test]
+fn draw_until_playable_stops_at_playable_card() {
+    let rules = Rules {
+        draw_until_playable: true,
+        ..Default::default()
+    };
+    let mut uno = MatchBuilder::new(4)
+        .rules(rules)
+        .hand(0, unplayable_hand())
+        .build();
+    let player_id = uno.get_current_turn_player_id();
+
+    let turn_action_result = uno.play_turn(player_id, TurnAction::Draw);
+    let TurnActionResult::DrewCards(drawn_count) = turn_action_result.turn_action_result else {
+        panic!("Expected cards to be drawn, got {turn_action_result:?}");
+    };
+
+    let top_card = uno.get_last_played_card();
+    let hand = &uno.get_player(&player_id).unwrap().hand;
+    assert_eq!(hand.len(), 7 + drawn_count);
+    let (last_card, other_cards) = hand.split_last().unwrap();
+    assert!(last_card.card.can_be_played_on(top_card));
+    assert!(other_cards
+        .iter()
+        .all(|x| !x.card.can_be_played_on(top_card)));
+    assert_eq!(uno.get_current_turn_player_id(), player_id);
+}
+
+#[test]
+fn draw_until_playable_can_play_drawn_card() {
+    let rules = Rules {
+        draw_until_playable: true,
+        play_drawn_card: true,
+        ..Default::default()
+    };
+    let mut uno = MatchBuilder::new(4)
+        .rules(rules)
+        .hand(0, unplayable_hand())
+        .build();
+    let player_id = uno.get_current_turn_player_id();
+
+    let turn_action_result = uno.play_turn(player_id, TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::DrewAndPlayed(2, Box::new(TurnActionResult::Neutral))
+    );
+    assert_eq!(
+        uno.get_last_played_card(),
+        &PlayedCard::Colored(CardColor::Yellow, ColoredCard::Number(9))
+    );
+    assert_eq!(uno.get_player(&player_id).unwrap().cards_count(), 8);
+    assert_ne!(uno.get_current_turn_player_id(), player_id);
+}
+
+#[test]
+fn starting_skip_skips_first_player() {
+    let uno = MatchBuilder::new(4)
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Skip))
+        .build();
+
+    assert_eq!(
+        uno.get_starting_card_effect(),
+        &StartingCardEffect::Skip { player_id: 0 }
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+#[test]
+fn starting_reverse_starts_with_last_player() {
+    let uno = MatchBuilder::new(4)
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Reverse))
+        .build();
+
+    assert_eq!(uno.get_starting_card_effect(), &StartingCardEffect::Reverse);
+    assert_eq!(uno.get_current_turn_player_id(), 3);
+    assert_eq!(uno.get_next_turn_player_id(), 2);
+}
+
+#[test]
+fn starting_draw_penalizes_first_player() {
+    let uno = MatchBuilder::new(4)
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Draw))
+        .build();
+
+    assert_eq!(
+        uno.get_starting_card_effect(),
+        &StartingCardEffect::Draw { player_id: 0 }
+    );
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 9);
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+}
+
+#[test]
+fn starting_wild_waits_for_color() {
+    let mut uno = MatchBuilder::new(4).top_card(Card::Wild).build();
+    assert_eq!(
+        uno.get_starting_card_effect(),
+        &StartingCardEffect::Wild { player_id: 0 }
+    );
+    assert!(uno.is_starting_color_pending());
+
+    let turn_action_result = uno.play_turn(0, TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::ColorNotChosen
+    );
+
+    let turn_action_result = uno.play_turn(0, TurnAction::ChooseColor(CardColor::Blue));
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::ColorChosen
+    );
+    assert_eq!(
+        uno.get_last_played_card(),
+        &PlayedCard::Wild(CardColor::Blue)
+    );
+    assert_eq!(uno.get_current_turn_player_id(), 0);
+
+    let turn_action_result = uno.play_turn(0, TurnAction::ChooseColor(CardColor::Red));
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::NoColorToChoose
+    );
+}
+
+#[test]
+fn match_never_starts_with_wild_draw() {
+    let rules = Rules {
+        deck: DeckSpec {
+            wild_draw: 400,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+
+    for seed in 0..20 {
+        let uno = MatchBuilder::new(4)
+            .rules(rules.clone())
+            .starting_player(StartingPlayer::Random)
+            .seed(seed)
+            .build();
+        assert!(!matches!(
+            uno.get_last_played_card(),
+            PlayedCard::WildDraw(_)
+        ));
+    }
+}
+
+/// Lets player 0 play a red `card` on a Red 5.
+fn play_action_card(player_count: usize, card: ColoredCard) -> (Uno, PlayTurnResult) {
+    let hand = vec![
+        Card::Colored(CardColor::Red, card),
+        Card::Colored(CardColor::Red, ColoredCard::Number(5)),
+    ];
+    let mut uno = MatchBuilder::new(player_count)
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Number(5)))
+        .hand(0, hand)
+        .build();
+    let card_id = uno.get_player(&0).unwrap().hand[0].id;
+
+    let result = uno.play_turn(
+        0,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    );
+
+    (uno, result)
+}
+
+#[test]
+fn action_cards_follow_official_rules_for_any_player_count() {
+    for player_count in [2, 3, 10] {
+        let players = player_count as u64;
+
+        let (uno, result) = play_action_card(player_count, ColoredCard::Skip);
+        assert_eq!(result.turn_action_result, TurnActionResult::Skip);
+        assert_eq!(
+            uno.get_current_turn_player_id(),
+            2 % players,
+            "Skip with {player_count} players"
+        );
+
+        // With two players a Reverse is played like a Skip
+        let (uno, result) = play_action_card(player_count, ColoredCard::Reverse);
+        let (expected_result, expected_player_id) = if player_count == 2 {
+            (TurnActionResult::Skip, 0)
+        } else {
+            (TurnActionResult::Reverse, players - 1)
+        };
+        assert_eq!(result.turn_action_result, expected_result);
+        assert_eq!(
+            uno.get_current_turn_player_id(),
+            expected_player_id,
+            "Reverse with {player_count} players"
+        );
+        if player_count > 2 {
+            assert_eq!(uno.get_next_turn_player_id(), players - 2);
+        }
+
+        let (uno, result) = play_action_card(player_count, ColoredCard::Draw);
+        assert_eq!(result.turn_action_result, TurnActionResult::Draw);
+        assert_eq!(
+            uno.get_current_turn_player_id(),
+            2 % players,
+            "Draw with {player_count} players"
+        );
+        assert_eq!(uno.get_player(&1).unwrap().cards_count(), 9);
+    }
+}
+
+/// Lets player 0 play their second-to-last card on a Red 9.
+fn play_second_to_last_card(say_uno: bool) -> Uno {
+    let red_five = Card::Colored(CardColor::Red, ColoredCard::Number(5));
+    let mut uno = MatchBuilder::new(4)
+        .top_card(Card::Colored(CardColor::Red, ColoredCard::Number(9)))
+        .hand(0, vec![red_five.clone(), red_five])
+        .build();
+    let card_id = uno.get_player(&0).unwrap().hand[0].id;
+
+    let result = uno.play_turn(
+        0,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: say_uno,
+        }),
+    );
+    assert_eq!(result.turn_action_result, TurnActionResult::Neutral);
+
+    uno
+}
+
+#[test]
+fn uno_can_be_said_with_second_to_last_card() {
+    let mut uno = play_second_to_last_card(true);
+
+    let caller_id = uno.get_current_turn_player_id();
+    let result = uno.play_turn(caller_id, TurnAction::Callout);
+
+    assert_eq!(result.turn_action_result, TurnActionResult::CalloutFailed);
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 1);
+    assert_eq!(uno.get_player(&caller_id).unwrap().cards_count(), 9);
+}
+
+#[test]
+fn uno_can_be_said_until_next_player_acts() {
+    let mut uno = play_second_to_last_card(false);
+    assert_ne!(uno.get_current_turn_player_id(), 0);
+
+    let result = uno.play_turn(0, TurnAction::Uno);
+    assert_eq!(result.turn_action_result, TurnActionResult::UnoSuccessful);
+
+    let result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Callout);
+    assert_eq!(result.turn_action_result, TurnActionResult::CalloutFailed);
+}
+
+#[test]
+fn anyone_can_call_out_before_next_player_acts() {
+    let mut uno = play_second_to_last_card(false);
+
+    // Neither the one that played nor the one whose turn it is now
+    let current_player_id = uno.get_current_turn_player_id();
+    let caller_id = (0..4).find(|x| *x != 0 && *x != current_player_id).unwrap();
+    let result = uno.play_turn(caller_id, TurnAction::Callout);
+
+    assert_eq!(
+        result.turn_action_result,
+        TurnActionResult::CalledOut(vec![0])
+    );
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 3);
+    assert_eq!(uno.get_current_turn_player_id(), current_player_id);
+}
+
+#[test]
+fn callout_window_closes_once_next_player_acts() {
+    let mut uno = play_second_to_last_card(false);
+
+    let next_player_id = uno.get_current_turn_player_id();
+    let result = uno.play_turn(next_player_id, TurnAction::Draw);
+    assert!(!result.turn_action_result.is_rejected());
+
+    let result = uno.play_turn(next_player_id, TurnAction::Callout);
+    assert_eq!(result.turn_action_result, TurnActionResult::CalloutFailed);
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 1);
+}
+
+#[test]
+fn callout_window_closes_once_next_player_is_skipped() {
+    let mut uno = play_second_to_last_card(false);
+
+    let next_player_id = uno.get_current_turn_player_id();
+    uno.skip_turn();
+
+    let result = uno.play_turn(next_player_id, TurnAction::Callout);
+    assert_eq!(result.turn_action_result, TurnActionResult::CalloutFailed);
+    assert_eq!(uno.get_player(&0).unwrap().cards_count(), 1);
+}
+
+#[test]
+fn host_goes_first() {
+    let uno = MatchBuilder::new(4)
+        .starting_player(StartingPlayer::Host)
+        .host(Some(2))
+        .build();
+    let choice = uno.get_starting_player();
+    assert_eq!(choice.player_id, 2);
+    assert_eq!(choice.policy, StartingPlayer::Host);
+}
+
+#[test]
+fn player_left_of_dealer_goes_first() {
+    let uno = MatchBuilder::new(4)
+        .starting_player(StartingPlayer::LeftOfDealer)
+        .host(None)
+        .dealer(Some(1))
+        .build();
+    let choice = uno.get_starting_player();
+    assert_eq!(choice.player_id, 2);
+    assert_eq!(choice.policy, StartingPlayer::LeftOfDealer);
+
+    let uno = MatchBuilder::new(4)
+        .starting_player(StartingPlayer::LeftOfDealer)
+        .host(None)
+        .dealer(Some(3))
+        .build();
+    let choice = uno.get_starting_player();
+    assert_eq!(choice.player_id, 0);
+}
+
+#[test]
+fn missing_dealer_falls_back_to_random_player() {
+    let uno = MatchBuilder::new(4)
+        .starting_player(StartingPlayer::LeftOfDealer)
+        .host(Some(1))
+        .dealer(Some(10))
+        .build();
+    let choice = uno.get_starting_player();
+    assert_eq!(choice.policy, StartingPlayer::Random);
+}
+
+#[test]
+fn random_starting_player_depends_on_seed() {
+    let starting_player_ids = (0..20)
+        .map(|seed| {
+            let uno = MatchBuilder::new(4)
+                .starting_player(StartingPlayer::Random)
+                .seed(seed)
+                .build();
+            uno.get_starting_player().player_id
+        })
+        .collect::<BTreeSet<_>>();
+    assert!(starting_player_ids.len() > 1);
+}
+
+#[test]
+fn highest_card_goes_first() {
+    let uno = MatchBuilder::new(4)
+        .starting_player(StartingPlayer::HighestCard)
+        .host(None)
+        .build();
+    let choice = uno.get_starting_player();
+    assert_eq!(choice.policy, StartingPlayer::HighestCard);
+
+    let value = |card: &Card| match card {
+        Card::Colored(_, ColoredCard::Number(number)) => *number,
+        _ => 0,
+    };
+    let last_round = choice.draws.last().expect("Cards must have been drawn.");
+    let (_, winning_card) = last_round
+        .iter()
+        .find(|(player_id, _)| *player_id == choice.player_id)
+        .expect("The starting player must have drawn in the last round.");
+    assert!(last_round.iter().all(
+        |(player_id, card)| *player_id == choice.player_id || value(card) < value(winning_card)
+    ));
+}
+
+fn seated_match() -> MatchBuilder {
+    MatchBuilder::new(3).seats(&[(30, "Carol"), (10, "Alice"), (20, "Bob")])
+}
+
+#[test]
+fn turns_follow_seats_instead_of_ids() {
+    let mut uno = seated_match().build();
+    assert_eq!(uno.get_player_ids(), vec![30, 10, 20]);
+    assert_eq!(uno.get_current_turn_player_id(), 30);
+    assert_eq!(uno.get_next_turn_player_id(), 10);
+
+    uno.skip_turn();
+    assert_eq!(uno.get_next_turn_player_id(), 20);
+}
+
+#[test]
+fn joining_player_takes_last_seat() {
+    let mut uno = seated_match().build();
+    uno.add_player(5, "Dave".to_string()).unwrap();
+
+    assert_eq!(uno.get_player_ids(), vec![30, 10, 20, 5]);
+    assert_eq!(uno.get_current_turn_player_id(), 30);
+    assert_eq!(uno.get_turn_distance(&5), Some(3));
+}
+
+#[test]
+fn seats_must_be_unique() {
+    let error = MatchBuilder::new(2)
+        .seats(&[(1, "Alice"), (1, "Alice")])
+        .try_build()
+        .unwrap_err();
+    assert_eq!(error, UnoError::PlayerAlreadyExists);
+}
+
+/// Sets up a match of team 0 with players 0 and 1 against team 1 with players 2 and 3.
+fn team_match(team_win: TeamWin) -> MatchBuilder {
+    MatchBuilder::new(4)
+        .teams(&[&[0, 1], &[2, 3]])
+        .rules(Rules {
+            team_win,
+            ..Default::default()
+        })
+}
+
+/// Lets the current player go out with a number card of the top color.
+fn go_out(uno: &mut Uno) -> PlayTurnResult {
+    let color = uno.get_last_played_card().color();
+    let player_id = uno.get_current_turn_player_id();
+    let player = uno
+        .get_player_mut(&player_id)
+        .expect("Current player must exist.");
+    player.hand.truncate(1);
+    player.hand[0].card = Card::Colored(color, ColoredCard::Number(5));
+    let card_id = player.hand[0].id;
+
+    uno.play_turn(
+        player_id,
+        TurnAction::Play(PlayAction::Card {
+            card_id,
+            chosen_color: None,
+            uno: false,
+        }),
+    )
+}
+
+#[test]
+fn partners_sit_opposite_each_other() {
+    let uno = team_match(TeamWin::AnyMember).build();
+    assert_eq!(uno.get_player_ids(), vec![0, 2, 1, 3]);
+    assert_eq!(uno.get_teams(), [vec![0, 1], vec![2, 3]]);
+    assert_eq!(uno.get_team(&1), Some(0));
+    assert_eq!(uno.get_team(&3), Some(1));
+    assert!(!uno.is_over());
+}
+
+#[test]
+fn team_wins_when_any_member_goes_out() {
+    let mut uno = team_match(TeamWin::AnyMember).build();
+    let score = [2, 3]
+        .iter()
+        .flat_map(|x| &uno.get_player(x).unwrap().hand)
+        .map(|x| x.card.points())
+        .sum::<u32>();
+
+    assert!(go_out(&mut uno).won);
+    assert_eq!(uno.get_winning_team(), Some(0));
+    assert_eq!(uno.get_team_score(0), score);
+    assert!(uno.is_over());
+
+    let turn_action_result = uno.play_turn(uno.get_current_turn_player_id(), TurnAction::Draw);
+    assert_eq!(
+        turn_action_result.turn_action_result,
+        TurnActionResult::MatchOver
+    );
+}
+
+#[test]
+fn team_wins_when_all_members_go_out() {
+    let mut uno = team_match(TeamWin::AllMembers).build();
+
+    assert!(go_out(&mut uno).won);
+    assert_eq!(uno.get_winning_team(), None);
+    assert!(!uno.is_over());
+
+    assert_eq!(uno.get_player_ids(), vec![2, 1, 3]);
+    assert_eq!(uno.get_current_turn_player_id(), 2);
+
+    uno.skip_turn();
+    assert_eq!(uno.get_current_turn_player_id(), 1);
+    assert!(go_out(&mut uno).won);
+    assert_eq!(uno.get_winning_team(), Some(0));
+    assert!(uno.is_over());
+}
+
+#[test]
+fn teams_must_be_even() {
+    let teams: [&[&[u64]]; 3] = [&[&[0, 1], &[2, 3, 4]], &[&[0, 1, 2, 3]], &[&[0, 1], &[]]];
+    for teams in teams {
+        let error = MatchBuilder::new(5).teams(teams).try_build().unwrap_err();
+        assert_eq!(error, UnoError::InvalidTeams);
+    }
+}
+
+#[test]
+fn players_cannot_join_team_match() {
+    let mut uno = team_match(TeamWin::AnyMember).build();
+    let error = uno.add_player(4, "Eve".to_string()).unwrap_err();
+    assert_eq!(error, UnoError::TeamMatchStarted);
+}
@@ -1,4 +1,5 @@
 use convert_case::{Case, Converter};
+use match_service::{error::MatchError, handle::MatchSnapshot};
 use poise::{
     serenity_prelude::{
         ButtonStyle, ComponentType, Context, CreateComponents, Interaction,
@@ -8,15 +9,19 @@ use poise::{
 };
 use runo::{
     card::{Card, CardColor},
-    turn::{PlayAction, TurnAction},
-    uno::Uno,
+    turn::{PlayAction, TurnAction, TurnActionResult},
 };
 use strum::IntoEnumIterator;
 use strum_macros::{Display, EnumIter, EnumString};
 
-use crate::{Data, UnoGame};
+use crate::Data;
 
-use super::select_menu::{CardSelectMenu, ColorSelectMenu, SelectMenu};
+use super::{
+    error::{report_error, Result, UnoCommandError},
+    hand_view::{handle_quick_play, handle_view_hand, QuickPlay},
+    select_menu::{CardSelectMenu, ColorSelectMenu, PlayerSelectMenu, SelectMenu},
+    spectate::handle_spectate,
+};
 
 #[derive(Debug, Display, EnumString, EnumIter)]
 pub enum UnoButton {
@@ -25,78 +30,88 @@ pub enum UnoButton {
     Draw,
     Uno,
     Callout,
+    Challenge,
+    SwapHands,
+    ChooseColor,
+    Spectate,
 }
 
 impl UnoButton {
-    fn custom_id(&self) -> String {
+    pub(super) fn custom_id(&self) -> String {
         let converter = Converter::new()
             .from_case(Case::Pascal)
             .to_case(Case::Snake);
         converter.convert(format!("{self}"))
     }
 
-    fn label(&self) -> String {
+    pub(super) fn label(&self) -> String {
         let converter = Converter::new()
             .from_case(Case::Pascal)
             .to_case(Case::Title);
         converter.convert(format!("{self}"))
     }
-    pub fn create_components(c: &mut CreateComponents) -> &mut CreateComponents {
-        c.create_action_row(|ar| {
-            for (index, variant) in Self::iter().enumerate() {
-                ar.create_button(|b| {
-                    b.label(variant.label())
-                        .style(if index == 0 {
-                            ButtonStyle::Primary
-                        } else {
-                            ButtonStyle::Secondary
-                        })
-                        .custom_id(variant.custom_id())
-                });
-            }
-            ar
-        })
+    /// Whether the button does anything in the match, e.g. [`UnoButton::Challenge`] only does
+    /// with [`Rules::wild_draw_challenge`](runo::rules::Rules::wild_draw_challenge).
+    fn is_available(&self, snapshot: &MatchSnapshot) -> bool {
+        match self {
+            Self::Challenge => snapshot.rules.wild_draw_challenge,
+            Self::SwapHands => snapshot.rules.seven_zero,
+            Self::ChooseColor => snapshot.starting_color_pending,
+            _ => true,
+        }
     }
 
-    async fn process(ctx: &Context, interaction: &MessageComponentInteraction, data: &Data) {
-        let Some(button_type) = Self::iter().find(|x| x.custom_id() == interaction.data.custom_id) else {
-            return;
-        };
-        let mut matches = data.matches.lock().await;
-
-        let Some(game) = matches.get_mut(&interaction.channel_id) else {
-            return;
-        };
-
-        match game {
-            UnoGame::Pending { .. } => todo!(),
-            UnoGame::Ongoing { game, .. } => {
-                // Let players view their cards any time
-                if !matches!(button_type, Self::ViewHand) {
-                    let current_player_id = game.get_current_turn_player_id();
-                    if current_player_id != interaction.user.id.0 {
-                        interaction
-                            .create_interaction_response(ctx, |ir| {
-                                ir.kind(InteractionResponseType::ChannelMessageWithSource)
-                                    .interaction_response_data(|ird| {
-                                        ird.ephemeral(true).content("Please wait for your turn.")
-                                    })
+    pub fn create_components<'a>(
+        c: &'a mut CreateComponents,
+        snapshot: &MatchSnapshot,
+    ) -> &'a mut CreateComponents {
+        let variants = Self::iter()
+            .filter(|x| x.is_available(snapshot))
+            .collect::<Vec<_>>();
+        // Discord allows at most 5 buttons per action row
+        for (row_index, row) in variants.chunks(5).enumerate() {
+            c.create_action_row(|ar| {
+                for (index, variant) in row.iter().enumerate() {
+                    ar.create_button(|b| {
+                        b.label(variant.label())
+                            .style(if row_index == 0 && index == 0 {
+                                ButtonStyle::Primary
+                            } else {
+                                ButtonStyle::Secondary
                             })
-                            .await
-                            .unwrap();
-                        return;
-                    }
+                            .custom_id(variant.custom_id())
+                    });
                 }
+                ar
+            });
+        }
+        c
+    }
 
-                match button_type {
-                    Self::PlayCard => handle_play_card(ctx, interaction, game).await,
-                    Self::ViewHand => handle_view_hand(ctx, interaction, game).await,
-                    Self::Draw => handle_draw(ctx, interaction, game).await,
-                    Self::Uno => handle_say_uno(ctx, interaction, game).await,
-                    Self::Callout => handle_callout(ctx, interaction, game).await,
-                }
-            }
+    async fn process(
+        ctx: &Context,
+        interaction: &MessageComponentInteraction,
+        data: &Data,
+    ) -> Result<()> {
+        if let Some(quick_play) = QuickPlay::from_custom_id(&interaction.data.custom_id) {
+            return handle_quick_play(ctx, interaction, data, quick_play).await;
+        }
+
+        let Some(button_type) = Self::iter().find(|x| x.custom_id() == interaction.data.custom_id) else {
+            return Ok(());
         };
+
+        match button_type {
+            Self::PlayCard => handle_play_card(ctx, interaction, data).await,
+            Self::ViewHand => handle_view_hand(ctx, interaction, data).await,
+            Self::Draw => handle_draw(ctx, interaction, data).await,
+            Self::Uno => handle_say_uno(ctx, interaction, data).await,
+            Self::Callout => handle_callout(ctx, interaction, data).await,
+            Self::Challenge => handle_challenge(ctx, interaction, data).await,
+            Self::SwapHands => handle_swap_hands(ctx, interaction, data).await,
+            Self::ChooseColor => handle_choose_color(ctx, interaction, data).await,
+            Self::Spectate => handle_spectate(ctx, interaction, data).await,
+        }
     }
 
     pub async fn handle_event<'a>(ctx: &Context, event: &Event<'_>, data: &Data) {
@@ -105,7 +120,9 @@ impl UnoButton {
         } = event
         {
             if let ComponentType::Button = component_interaction.data.component_type {
-                Self::process(ctx, component_interaction, data).await
+                if let Err(err) = Self::process(ctx, component_interaction, data).await {
+                    report_error(ctx, component_interaction, &err).await;
+                }
             }
         }
     }
@@ -114,134 +131,318 @@ impl UnoButton {
 async fn handle_play_card(
     ctx: &Context,
     interaction: &MessageComponentInteraction,
-    game: &mut Uno,
-) {
-    let Some(player) = game.get_player(&interaction.user.id.0) else {
-        return
-    };
-
-    let player_cards = player.hand.clone();
-    let mut card_select_menu = CardSelectMenu::new(player_cards.as_slice());
-    let interaction = card_select_menu
-        .await_selection(ctx, interaction)
-        .await
-        .unwrap()
-        .unwrap();
+    data: &Data,
+) -> Result<()> {
+    let user_id = interaction.user.id;
 
-    let chosen_card = card_select_menu.get_selection().unwrap();
-
-    match chosen_card {
-        Card::Colored(_, _) => {
-            let result = game.play_turn(TurnAction::Play(PlayAction::ColoredCard(
-                chosen_card.clone(),
-            )));
+    let snapshot = data
+        .matches
+        .player_snapshot(&interaction.channel_id, user_id)
+        .await?;
+    // Players may jump in out of turn, which the match checks once the card has been chosen
+    if snapshot.current_turn_player_id != user_id.0 && !snapshot.rules.jump_in {
+        return Err(MatchError::NotYourTurn.into());
+    }
+    let hand = snapshot.hand(user_id.0).unwrap_or_default();
+    // Only cards matching the color or symbol of the top card can be played
+    let playable_cards = hand
+        .iter()
+        .filter(|x| x.card.can_be_played_on(&snapshot.last_played_card))
+        .cloned()
+        .collect::<Vec<_>>();
+    if playable_cards.is_empty() {
+        return Err(UnoCommandError::NoPlayableCard);
+    }
 
-            let delete_original_response = interaction.delete_original_interaction_response(ctx);
+    // The match keeps running while waiting for the selections, it checks the turn again
+    // UNO has to be said along with the second-to-last card
+    let mut card_select_menu = CardSelectMenu::new(&playable_cards, hand.len() == 2);
+    let interaction = card_select_menu.await_selection(ctx, interaction).await?;
 
-            let create_followup_message = interaction.create_followup_message(ctx, |irf| {
-                irf.content(format!("You chose: {}, result: {:?}", chosen_card, result))
-                    .ephemeral(true)
-            });
+    let (chosen_card, uno) = card_select_menu
+        .get_selection()
+        .ok_or(UnoCommandError::InvalidSelection)?;
 
-            let (delete_original_response, create_followup_message) =
-                tokio::join!(delete_original_response, create_followup_message,);
-            delete_original_response.unwrap();
-            create_followup_message.unwrap();
-        }
-        _ => {
+    let (interaction, chosen_color) = match chosen_card.card {
+        Card::Colored(_, _) => (interaction, None),
+        Card::Wild | Card::WildDraw => {
             let colors = CardColor::iter().collect::<Vec<_>>();
 
             let mut color_select_menu = ColorSelectMenu::new(&colors);
-            let interaction = color_select_menu
-                .await_selection(ctx, &interaction)
-                .await
-                .unwrap()
-                .unwrap();
-            let color = color_select_menu.get_selection().unwrap();
-
-            let play_action = match chosen_card {
-                Card::Colored(_, _) => unreachable!(),
-                Card::Wild => PlayAction::WildDraw(color.clone()),
-                Card::WildDraw => PlayAction::WildDraw(color.clone()),
-            };
-
-            let result = game.play_turn(TurnAction::Play(play_action));
-            interaction
-                .create_interaction_response(ctx, |ir| {
-                    ir.kind(InteractionResponseType::ChannelMessageWithSource)
-                        .interaction_response_data(|ird| {
-                            ird.content(format!("You chose: {}, result: {:?}", chosen_card, result))
-                                .ephemeral(true)
-                        })
-                })
-                .await
-                .unwrap();
+            let color_interaction = color_select_menu.await_selection(ctx, &interaction).await?;
+            let color = color_select_menu
+                .get_selection()
+                .ok_or(UnoCommandError::InvalidSelection)?;
+
+            (color_interaction, Some(*color))
         }
+    };
+
+    let mut action = match chosen_color {
+        Some(color) => format!("played {chosen_card} and chose {color}"),
+        None => format!("played {chosen_card}"),
+    };
+    if uno {
+        action.push_str(" and said UNO");
+    }
+
+    let turn_action = TurnAction::Play(PlayAction::Card {
+        card_id: chosen_card.id,
+        chosen_color,
+        uno,
+    });
+    let result = data
+        .matches
+        .play_turn(&interaction.channel_id, user_id, turn_action)
+        .await?;
+
+    // The turn only moves on once the player chose whom to swap hands with
+    if result.turn_action_result == TurnActionResult::ChooseHandSwap {
+        return handle_swap_hands(ctx, &interaction, data).await;
     }
+
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|ird| {
+                    ird.content(format!("You {action}, result: {:?}", result))
+                        .components(|c| c)
+                })
+        })
+        .await?;
+
+    Ok(())
 }
 
-async fn handle_view_hand(ctx: &Context, interaction: &MessageComponentInteraction, game: &Uno) {
-    let Some(player) = game.get_player(&interaction.user.id.0) else {
-        return
+async fn handle_draw(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            interaction.user.id,
+            TurnAction::Draw,
+        )
+        .await?;
+    let content = match result.turn_action_result {
+        TurnActionResult::MustPlayCard => {
+            "You have a card you can play, so you can't draw.".to_string()
+        }
+        _ => format!("You chose to draw, result: {:?}", result),
     };
 
-    let cards_string = player
-        .hand
-        .iter()
-        .map(|x| x.to_string())
-        .collect::<Vec<_>>()
-        .join(", ");
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|ird| ird.content(content).ephemeral(true))
+        })
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_say_uno(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            interaction.user.id,
+            TurnAction::Uno,
+        )
+        .await?;
 
     interaction
         .create_interaction_response(ctx, |ir| {
             ir.kind(InteractionResponseType::ChannelMessageWithSource)
                 .interaction_response_data(|ird| {
-                    ird.content(format!("Your hand: {cards_string}."))
+                    ird.content(format!("You chose to say UNO, result: {:?}", result))
                         .ephemeral(true)
                 })
         })
-        .await
-        .unwrap();
+        .await?;
+
+    Ok(())
 }
 
-async fn handle_draw(ctx: &Context, interaction: &MessageComponentInteraction, game: &mut Uno) {
-    let result = game.play_turn(TurnAction::Draw);
+async fn handle_callout(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            interaction.user.id,
+            TurnAction::Callout,
+        )
+        .await?;
+
     interaction
         .create_interaction_response(ctx, |ir| {
             ir.kind(InteractionResponseType::ChannelMessageWithSource)
                 .interaction_response_data(|ird| {
-                    ird.content(format!("You chose to draw, result: {:?}", result))
+                    ird.content(format!("You chose do a callout, result: {:?}", result))
                         .ephemeral(true)
                 })
         })
-        .await
-        .unwrap();
+        .await?;
+
+    Ok(())
 }
 
-async fn handle_say_uno(ctx: &Context, interaction: &MessageComponentInteraction, game: &mut Uno) {
-    let result = game.play_turn(TurnAction::Uno);
+async fn handle_challenge(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            interaction.user.id,
+            TurnAction::Challenge,
+        )
+        .await?;
+
     interaction
         .create_interaction_response(ctx, |ir| {
             ir.kind(InteractionResponseType::ChannelMessageWithSource)
                 .interaction_response_data(|ird| {
-                    ird.content(format!("You chose to say UNO, result: {:?}", result))
-                        .ephemeral(true)
+                    ird.content(format!(
+                        "You challenged the Wild Draw (+4), result: {:?}",
+                        result
+                    ))
+                    .ephemeral(true)
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+async fn handle_swap_hands(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let user_id = interaction.user.id;
+
+    let snapshot = data
+        .matches
+        .player_snapshot(&interaction.channel_id, user_id)
+        .await?;
+    if snapshot.pending_hand_swap != Some(user_id.0) {
+        interaction
+            .create_interaction_response(ctx, |ir| {
+                ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|ird| {
+                        ird.content("You can only swap hands right after playing a 7.")
+                            .ephemeral(true)
+                    })
+            })
+            .await?;
+        return Ok(());
+    }
+    let other_players = snapshot
+        .names
+        .into_iter()
+        .filter(|(id, _)| *id != user_id.0)
+        .collect::<Vec<_>>();
+
+    let mut player_select_menu = PlayerSelectMenu::new(&other_players);
+    let interaction = player_select_menu.await_selection(ctx, interaction).await?;
+    let (target_id, target_name) = player_select_menu
+        .get_selection()
+        .ok_or(UnoCommandError::InvalidSelection)?;
+
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            user_id,
+            TurnAction::SwapHands(*target_id),
+        )
+        .await?;
+
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|ird| {
+                    ird.content(format!(
+                        "You swapped hands with {target_name}, result: {:?}",
+                        result
+                    ))
+                    .components(|c| c)
                 })
         })
-        .await
-        .unwrap();
+        .await?;
+
+    Ok(())
 }
 
-async fn handle_callout(ctx: &Context, interaction: &MessageComponentInteraction, game: &mut Uno) {
-    let result = game.play_turn(TurnAction::Callout);
+async fn handle_choose_color(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let user_id = interaction.user.id;
+
+    let snapshot = data
+        .matches
+        .player_snapshot(&interaction.channel_id, user_id)
+        .await?;
+    if snapshot.current_turn_player_id != user_id.0 {
+        return Err(MatchError::NotYourTurn.into());
+    }
+
+    // The color can only be chosen once, so the button is taken off the message afterwards
+    let mut buttons_message = interaction.message.clone();
+
+    let colors = CardColor::iter().collect::<Vec<_>>();
+    let mut color_select_menu = ColorSelectMenu::new(&colors);
+    let interaction = color_select_menu.await_selection(ctx, interaction).await?;
+    let color = color_select_menu
+        .get_selection()
+        .ok_or(UnoCommandError::InvalidSelection)?;
+
+    let result = data
+        .matches
+        .play_turn(
+            &interaction.channel_id,
+            user_id,
+            TurnAction::ChooseColor(*color),
+        )
+        .await?;
+
     interaction
         .create_interaction_response(ctx, |ir| {
-            ir.kind(InteractionResponseType::ChannelMessageWithSource)
+            ir.kind(InteractionResponseType::UpdateMessage)
                 .interaction_response_data(|ird| {
-                    ird.content(format!("You chose do a callout, result: {:?}", result))
-                        .ephemeral(true)
+                    ird.content(format!("You chose {color}, result: {:?}", result))
+                        .components(|c| c)
                 })
         })
-        .await
-        .unwrap();
+        .await?;
+
+    if !result.turn_action_result.is_rejected() {
+        let snapshot = data
+            .matches
+            .player_snapshot(&interaction.channel_id, user_id)
+            .await?;
+        buttons_message
+            .edit(ctx, |m| {
+                m.components(|c| UnoButton::create_components(c, &snapshot))
+            })
+            .await?;
+    }
+
+    Ok(())
 }
@@ -0,0 +1,125 @@
+use match_service::error::MatchError;
+use poise::serenity_prelude::{
+    self as serenity, Context, HttpError, InteractionResponseType, MessageComponentInteraction,
+};
+use thiserror::Error;
+use tracing::{error, info, warn};
+
+/// Discord's JSON error code for an interaction whose token is no longer valid.
+const UNKNOWN_INTERACTION: isize = 10062;
+
+#[derive(Error, Debug)]
+pub enum UnoCommandError {
+    #[error("Discord request failed: {0}")]
+    Serenity(#[from] serenity::Error),
+    #[error("No selection was made in time")]
+    SelectionTimedOut,
+    #[error("Received an unexpected interaction while waiting for a selection")]
+    UnexpectedInteraction,
+    #[error("The selected option does not exist")]
+    InvalidSelection,
+    #[error("None of the cards in the hand can be played")]
+    NoPlayableCard,
+    #[error(transparent)]
+    Match(#[from] MatchError),
+}
+
+impl UnoCommandError {
+    /// Whether the interaction expired, in which case there is nothing left to reply to.
+    pub fn is_expired_interaction(&self) -> bool {
+        match self {
+            Self::Serenity(serenity::Error::Http(http_error)) => matches!(
+                http_error.as_ref(),
+                HttpError::UnsuccessfulRequest(response)
+                    if response.error.code == UNKNOWN_INTERACTION
+            ),
+            _ => false,
+        }
+    }
+
+    pub fn user_message(&self) -> String {
+        match self {
+            Self::Serenity(_) => {
+                "Something went wrong while talking to Discord, please try again.".to_string()
+            }
+            Self::SelectionTimedOut => "You took too long to choose, please try again.".to_string(),
+            Self::UnexpectedInteraction | Self::InvalidSelection => {
+                "That selection is no longer valid, please try again.".to_string()
+            }
+            Self::NoPlayableCard => {
+                "None of your cards can be played on the top card, draw instead.".to_string()
+            }
+            Self::Match(MatchError::NotFound) => {
+                "There's no match in this channel anymore.".to_string()
+            }
+            Self::Match(MatchError::NotStarted) => {
+                "The match in this channel hasn't started yet, wait for the host to start it."
+                    .to_string()
+            }
+            Self::Match(MatchError::Ended(_)) => {
+                "The match in this channel has already ended.".to_string()
+            }
+            Self::Match(MatchError::NotInMatch) => {
+                "You are not playing in this match. Press Spectate to watch it.".to_string()
+            }
+            Self::Match(MatchError::NotYourTurn) => "Please wait for your turn.".to_string(),
+            Self::Match(MatchError::JumpInLost) => {
+                "Someone closer in turn order jumped in with the same card first.".to_string()
+            }
+        }
+    }
+
+    /// Whether the error was caused by the user rather than by something going wrong.
+    fn is_user_error(&self) -> bool {
+        !matches!(
+            self,
+            Self::Serenity(_) | Self::UnexpectedInteraction | Self::InvalidSelection
+        )
+    }
+}
+
+pub type Result<T, E = UnoCommandError> = std::result::Result<T, E>;
+
+/// Logs the error and lets the user know about it with an ephemeral message, if the interaction
+/// can still be responded to.
+pub async fn report_error(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    error: &UnoCommandError,
+) {
+    match error {
+        _ if error.is_expired_interaction() => {
+            warn!(
+                "Interaction {} expired before it was handled: {error}",
+                interaction.id
+            );
+            return;
+        }
+        _ if error.is_user_error() => {
+            info!("Interaction {} was rejected: {error}", interaction.id)
+        }
+        _ => error!("Failed to handle interaction {}: {error}", interaction.id),
+    }
+
+    let content = error.user_message();
+
+    let response_result = interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|ird| ird.ephemeral(true).content(&content))
+        })
+        .await;
+
+    // The interaction has most likely been responded to already, so follow up on it instead
+    if response_result.is_err() {
+        if let Err(err) = interaction
+            .create_followup_message(ctx, |f| f.ephemeral(true).content(&content))
+            .await
+        {
+            error!(
+                "Failed to report error to the user of interaction {}: {err}",
+                interaction.id
+            );
+        }
+    }
+}
@@ -0,0 +1,483 @@
+use std::{fmt::Write as _, time::Instant};
+
+use match_service::{
+    error::MatchError,
+    handle::{MatchEvent, MatchSnapshot},
+};
+use poise::serenity_prelude::{
+    ButtonStyle, ChannelId, Context, CreateComponents, InteractionResponseType,
+    MessageComponentInteraction, UserId,
+};
+use runo::{
+    card::{Card, CardColor, CardId, PlayedCard},
+    turn::{PlayAction, TurnAction},
+};
+use strum::IntoEnumIterator;
+use tokio::sync::broadcast::{error::RecvError, Receiver};
+
+use crate::Data;
+
+use super::{button::UnoButton, error::Result, spectate::REFRESH_DURATION, AsEmoji};
+
+const QUICK_PLAY_PREFIX: &str = "quick_play_";
+const QUICK_PLAY_UNO_SUFFIX: &str = "uno";
+// Discord allows 5 rows of 5 buttons per message, the last row is kept for Draw and UNO
+const MAX_QUICK_PLAY_BUTTONS: usize = 20;
+
+/// Playing a card with a single click. Wild cards get one quick play per color, and with two
+/// cards left every card also gets one that says UNO along with it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QuickPlay {
+    card_id: CardId,
+    chosen_color: Option<CardColor>,
+    uno: bool,
+}
+
+impl QuickPlay {
+    fn custom_id(&self) -> String {
+        let mut custom_id = format!("{QUICK_PLAY_PREFIX}{}", self.card_id);
+        if let Some(color) = self.chosen_color {
+            let _ = write!(custom_id, "_{color}");
+        }
+        if self.uno {
+            let _ = write!(custom_id, "_{QUICK_PLAY_UNO_SUFFIX}");
+        }
+        custom_id
+    }
+
+    pub fn from_custom_id(custom_id: &str) -> Option<Self> {
+        let mut parts = custom_id
+            .strip_prefix(QUICK_PLAY_PREFIX)?
+            .split('_')
+            .peekable();
+        let card_id = parts.next()?.parse().ok()?;
+        let chosen_color = match parts.next_if(|x| *x != QUICK_PLAY_UNO_SUFFIX) {
+            Some(color) => Some(color.parse().ok()?),
+            None => None,
+        };
+        let uno = parts.next_if_eq(&QUICK_PLAY_UNO_SUFFIX).is_some();
+        if parts.next().is_some() {
+            return None;
+        }
+
+        Some(Self {
+            card_id,
+            chosen_color,
+            uno,
+        })
+    }
+}
+
+/// A player's own view of the match, with a button for every distinct card they can play.
+#[derive(Debug, PartialEq)]
+struct HandView {
+    content: String,
+    is_their_turn: bool,
+    can_challenge: bool,
+    /// Whether the user played a 7 and has to choose whom to swap hands with.
+    can_swap_hands: bool,
+    /// Whether the match started with a wild card and the user has to choose its color.
+    can_choose_color: bool,
+    /// Drawing is not allowed while holding a playable card, see `Rules::must_play_if_able`.
+    can_draw: bool,
+    /// UNO can still be said after the turn moved on, until the next player acts.
+    can_say_uno: bool,
+    draw_label: String,
+    /// The card the user may play out of turn, see `Rules::jump_in`.
+    jump_in_card: Option<Card>,
+    quick_plays: Vec<(QuickPlay, Card)>,
+}
+
+impl HandView {
+    fn new(snapshot: &MatchSnapshot, user_id: UserId) -> Option<Self> {
+        let hand = snapshot.hand(user_id.0)?;
+        let is_their_turn = snapshot.current_turn_player_id == user_id.0;
+        let can_challenge = snapshot
+            .pending_wild_draw
+            .as_ref()
+            .is_some_and(|x| x.victim_id == user_id.0);
+        let can_swap_hands = snapshot.pending_hand_swap == Some(user_id.0);
+        let can_choose_color = is_their_turn && snapshot.starting_color_pending;
+        let jump_in_card = match &snapshot.last_played_card {
+            PlayedCard::Colored(color, card)
+                if snapshot.rules.jump_in
+                    && !is_their_turn
+                    && snapshot.pending_wild_draw.is_none() =>
+            {
+                Some(Card::Colored(*color, card.clone()))
+            }
+            _ => None,
+        }
+        .filter(|card| hand.iter().any(|x| x.card == *card));
+
+        let mut quick_plays = Vec::new();
+        let mut seen_cards = Vec::new();
+        for deck_card in hand {
+            let card = &deck_card.card;
+            if !card.can_be_played_on(&snapshot.last_played_card) || seen_cards.contains(&card) {
+                continue;
+            }
+            seen_cards.push(card);
+
+            let chosen_colors = match card {
+                Card::Colored(_, _) => vec![None],
+                Card::Wild | Card::WildDraw => CardColor::iter().map(Some).collect(),
+            };
+            for chosen_color in chosen_colors {
+                quick_plays.push((
+                    QuickPlay {
+                        card_id: deck_card.id,
+                        chosen_color,
+                        uno: false,
+                    },
+                    card.clone(),
+                ));
+            }
+        }
+        // UNO has to be said along with the second-to-last card, so it gets its own quick plays
+        if hand.len() == 2 {
+            let uno_quick_plays = quick_plays
+                .iter()
+                .map(|(quick_play, card)| {
+                    let quick_play = QuickPlay {
+                        uno: true,
+                        ..*quick_play
+                    };
+                    (quick_play, card.clone())
+                })
+                .collect::<Vec<_>>();
+            quick_plays.extend(uno_quick_plays);
+        }
+
+        // The Wild Draw (+4), the 7 or the starting wild card has to be dealt with before any
+        // card can be played
+        if can_challenge || can_swap_hands || snapshot.starting_color_pending {
+            quick_plays.clear();
+        }
+
+        // Accepting a Wild Draw (+4) is always allowed
+        let can_draw = can_challenge || !snapshot.rules.must_play_if_able || quick_plays.is_empty();
+        let can_say_uno = is_their_turn || hand.len() == 1;
+        let draw_label = match &snapshot.rules {
+            _ if can_challenge => UnoButton::Draw.label(),
+            rules if rules.draw_until_playable && rules.play_drawn_card => {
+                "Draw and Play".to_string()
+            }
+            rules if rules.draw_until_playable => "Draw Until Playable".to_string(),
+            _ => UnoButton::Draw.label(),
+        };
+
+        let mut content = String::new();
+        if snapshot.starting_color_pending {
+            let _ = writeln!(content, "Top card: Wild, its color is yet to be chosen");
+        } else {
+            let _ = writeln!(content, "Top card: {}", snapshot.last_played_card);
+        }
+        if is_their_turn {
+            let _ = writeln!(content, "It's your turn!");
+            if can_challenge {
+                let _ = writeln!(
+                    content,
+                    "A Wild Draw (+4) was played on you, Draw to accept it or Challenge it."
+                );
+            }
+            if can_swap_hands {
+                let _ = writeln!(content, "You played a 7, choose whom to swap hands with.");
+            }
+            if can_choose_color {
+                let _ = writeln!(
+                    content,
+                    "The match started with a Wild, choose the color to start with."
+                );
+            }
+        } else {
+            let _ = writeln!(
+                content,
+                "Waiting for <@{}>.",
+                snapshot.current_turn_player_id
+            );
+            if let Some(card) = &jump_in_card {
+                let _ = writeln!(content, "You can jump in with your {card}!");
+            }
+        }
+        let hand = hand
+            .iter()
+            .map(|x| x.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(content, "Your hand: {hand}.");
+        if quick_plays.len() > MAX_QUICK_PLAY_BUTTONS {
+            quick_plays.truncate(MAX_QUICK_PLAY_BUTTONS);
+            let _ = write!(
+                content,
+                "\nNot every card fits here, use Play Card for the rest."
+            );
+        }
+
+        Some(Self {
+            content,
+            is_their_turn,
+            can_challenge,
+            can_swap_hands,
+            can_choose_color,
+            can_draw,
+            can_say_uno,
+            draw_label,
+            jump_in_card,
+            quick_plays,
+        })
+    }
+
+    fn create_components<'a>(&self, c: &'a mut CreateComponents) -> &'a mut CreateComponents {
+        for row in self.quick_plays.chunks(5) {
+            c.create_action_row(|ar| {
+                for (quick_play, card) in row {
+                    ar.create_button(|b| {
+                        let (mut label, color) = match (card, quick_play.chosen_color) {
+                            (Card::Colored(color, _), _) => (card.to_string(), Some(*color)),
+                            (_, Some(color)) => (format!("{card} ({color})"), Some(color)),
+                            (_, None) => (card.to_string(), None),
+                        };
+                        if quick_play.uno {
+                            label.push_str(" + UNO");
+                        }
+                        b.custom_id(quick_play.custom_id())
+                            .label(label)
+                            .style(color.map_or(ButtonStyle::Secondary, button_style))
+                            .emoji(color.map_or_else(|| card.as_emoji(), |x| x.as_emoji()))
+                            .disabled(
+                                !self.is_their_turn && self.jump_in_card.as_ref() != Some(card),
+                            )
+                    });
+                }
+                ar
+            });
+        }
+
+        c.create_action_row(|ar| {
+            let buttons = if self.can_challenge {
+                vec![UnoButton::Draw, UnoButton::Challenge]
+            } else if self.can_swap_hands {
+                vec![UnoButton::SwapHands]
+            } else if self.can_choose_color {
+                vec![UnoButton::ChooseColor]
+            } else {
+                vec![UnoButton::Draw, UnoButton::Uno]
+            };
+            for button in buttons {
+                ar.create_button(|b| {
+                    let (label, enabled) = match button {
+                        UnoButton::Draw => {
+                            (self.draw_label.clone(), self.is_their_turn && self.can_draw)
+                        }
+                        UnoButton::Uno => (button.label(), self.can_say_uno),
+                        _ => (button.label(), self.is_their_turn),
+                    };
+                    b.custom_id(button.custom_id())
+                        .label(label)
+                        .style(ButtonStyle::Secondary)
+                        .disabled(!enabled)
+                });
+            }
+            ar
+        })
+    }
+}
+
+/// Discord has no yellow buttons, so yellow cards get the neutral gray.
+fn button_style(color: CardColor) -> ButtonStyle {
+    match color {
+        CardColor::Red => ButtonStyle::Danger,
+        CardColor::Green => ButtonStyle::Success,
+        CardColor::Blue => ButtonStyle::Primary,
+        CardColor::Yellow => ButtonStyle::Secondary,
+    }
+}
+
+async fn get_hand_view(data: &Data, channel_id: ChannelId, user_id: UserId) -> Result<HandView> {
+    let snapshot = data.matches.player_snapshot(&channel_id, user_id).await?;
+    HandView::new(&snapshot, user_id).ok_or_else(|| MatchError::NotInMatch.into())
+}
+
+/// Waits until something happens in the match that changes the user's view and returns the new
+/// view. Returns `None` once the user is no longer playing or the view has been shown for too long.
+async fn wait_for_hand_view_change(
+    data: &Data,
+    channel_id: ChannelId,
+    user_id: UserId,
+    events: &mut Receiver<MatchEvent>,
+    current_view: &HandView,
+    started_at: Instant,
+) -> Option<HandView> {
+    loop {
+        let remaining = REFRESH_DURATION.checked_sub(started_at.elapsed())?;
+        match tokio::time::timeout(remaining, events.recv()).await {
+            Ok(Ok(_) | Err(RecvError::Lagged(_))) => {}
+            Ok(Err(RecvError::Closed)) | Err(_) => return None,
+        }
+
+        let view = get_hand_view(data, channel_id, user_id).await.ok()?;
+        if view != *current_view {
+            return Some(view);
+        }
+    }
+}
+
+pub async fn handle_view_hand(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let channel_id = interaction.channel_id;
+    let user_id = interaction.user.id;
+
+    // Subscribing first makes sure that nothing that happens after the first view is missed
+    let mut events = data.matches.subscribe(&channel_id).await?;
+    let mut view = get_hand_view(data, channel_id, user_id).await?;
+
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|ird| {
+                    ird.ephemeral(true)
+                        .content(&view.content)
+                        .components(|c| view.create_components(c))
+                })
+        })
+        .await?;
+
+    let started_at = Instant::now();
+    while let Some(new_view) =
+        wait_for_hand_view_change(data, channel_id, user_id, &mut events, &view, started_at).await
+    {
+        interaction
+            .edit_original_interaction_response(ctx, |r| {
+                r.content(&new_view.content)
+                    .components(|c| new_view.create_components(c))
+            })
+            .await?;
+        view = new_view;
+    }
+
+    Ok(())
+}
+
+pub async fn handle_quick_play(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+    quick_play: QuickPlay,
+) -> Result<()> {
+    let channel_id = interaction.channel_id;
+    let user_id = interaction.user.id;
+
+    let turn_action = TurnAction::Play(PlayAction::Card {
+        card_id: quick_play.card_id,
+        chosen_color: quick_play.chosen_color,
+        uno: quick_play.uno,
+    });
+    let result = data
+        .matches
+        .play_turn(&channel_id, user_id, turn_action)
+        .await?;
+
+    // The hand view refreshes by itself, this just shows the outcome right away
+    let content = format!("Result: {:?}", result);
+    let view = get_hand_view(data, channel_id, user_id).await.ok();
+
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::UpdateMessage)
+                .interaction_response_data(|ird| match &view {
+                    Some(view) => ird
+                        .content(format!("{content}\n{}", view.content))
+                        .components(|c| view.create_components(c)),
+                    // Players that won have no hand left to show
+                    None => ird.content(&content).components(|c| c),
+                })
+        })
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::uno::test_util::{create_snapshot, deck_cards};
+
+    #[test]
+    fn quick_play_custom_id_round_trips() {
+        for chosen_color in [None, Some(CardColor::Green)] {
+            for uno in [false, true] {
+                let quick_play = QuickPlay {
+                    card_id: 42,
+                    chosen_color,
+                    uno,
+                };
+                assert_eq!(
+                    QuickPlay::from_custom_id(&quick_play.custom_id()),
+                    Some(quick_play)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn quick_play_from_invalid_custom_id() {
+        for custom_id in [
+            "view_hand",
+            "quick_play_",
+            "quick_play_red",
+            "quick_play_1_Purple",
+            "quick_play_1_uno_Red",
+            "quick_play_1_Red_uno_uno",
+        ] {
+            assert_eq!(QuickPlay::from_custom_id(custom_id), None, "{custom_id}");
+        }
+    }
+
+    #[test]
+    fn hand_view_offers_playable_cards_once() {
+        let hand = deck_cards(&["r5", "g2", "r5", "w", "b7"]);
+        let snapshot = create_snapshot(vec![(1, hand), (2, deck_cards(&["y1"]))], "r1");
+
+        let view = HandView::new(&snapshot, UserId(1)).unwrap();
+        assert!(view.is_their_turn);
+        let quick_plays = view
+            .quick_plays
+            .iter()
+            .map(|(quick_play, _)| (quick_play.card_id, quick_play.chosen_color))
+            .collect::<Vec<_>>();
+        let mut expected = vec![(0, None)];
+        expected.extend(CardColor::iter().map(|color| (3, Some(color))));
+        assert_eq!(quick_plays, expected);
+        assert!(view.quick_plays.iter().all(|(x, _)| !x.uno));
+    }
+
+    #[test]
+    fn hand_view_offers_uno_with_two_cards_left() {
+        let snapshot = create_snapshot(
+            vec![(1, deck_cards(&["r5", "g2"])), (2, deck_cards(&["y1"]))],
+            "r1",
+        );
+
+        let view = HandView::new(&snapshot, UserId(1)).unwrap();
+        let quick_plays = view
+            .quick_plays
+            .iter()
+            .map(|(quick_play, _)| (quick_play.card_id, quick_play.uno))
+            .collect::<Vec<_>>();
+        assert_eq!(quick_plays, vec![(0, false), (0, true)]);
+    }
+
+    #[test]
+    fn hand_view_only_for_players() {
+        let snapshot = create_snapshot(vec![(1, deck_cards(&["r5"])), (2, Vec::new())], "r1");
+
+        assert!(HandView::new(&snapshot, UserId(3)).is_none());
+        let view = HandView::new(&snapshot, UserId(2)).unwrap();
+        assert!(!view.is_their_turn);
+        assert!(view.quick_plays.is_empty());
+    }
+}
@@ -1,17 +1,39 @@
-use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
 
-use poise::serenity_prelude::{ReactionType, UserId};
-use runo::{
-    card::{Card, CardColor},
-    error::UnoError,
+use match_service::service::{
+    CreateOutcome, JoinOutcome, LeaveOutcome, OpenHandsOutcome, StartOutcome, TeamOutcome,
 };
+use poise::serenity_prelude::{ReactionType, UserId};
+use runo::card::{Card, CardColor};
+
+use crate::{commands::uno::button::UnoButton, Context, Error};
 
-use crate::{commands::uno::button::UnoButton, Context, Error, UnoGame};
+use self::play::play;
+use self::spectate::{start_spectating, stop_spectating, wait_for_view_change};
 
 pub mod button;
+pub mod error;
+pub mod hand_view;
+pub mod play;
 pub mod select_menu;
+pub mod spectate;
+#[cfg(test)]
+mod test_util;
 
-#[poise::command(slash_command, prefix_command, subcommands("create", "join", "start"))]
+#[poise::command(
+    slash_command,
+    prefix_command,
+    subcommands(
+        "create",
+        "join",
+        "leave",
+        "start",
+        "team",
+        "play",
+        "spectate",
+        "open_hands"
+    )
+)]
 pub async fn uno(_ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
@@ -19,17 +41,21 @@ pub async fn uno(_ctx: Context<'_>) -> Result<(), Error> {
 /// Create a new UNO match in the current channel
 #[poise::command(prefix_command, slash_command)]
 pub async fn create(ctx: Context<'_>) -> Result<(), Error> {
-    let create_match_result = create_match(ctx).await;
+    let create_outcome = ctx
+        .data()
+        .matches
+        .create(ctx.channel_id(), ctx.author().id, ctx.author().tag())
+        .await;
 
-    match create_match_result {
-        CreateMatchResult::Created => {
+    match create_outcome {
+        CreateOutcome::Created => {
             ctx.send(|m| {
                 m.reply(true)
                     .content("Successfully created a match with you in it!")
             })
             .await?;
         }
-        CreateMatchResult::AlreadyExists(player_ids) => {
+        CreateOutcome::AlreadyExists(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true)
@@ -41,7 +67,7 @@ pub async fn create(ctx: Context<'_>) -> Result<(), Error> {
             })
             .await?;
         }
-        CreateMatchResult::AlreadyJoined(player_ids) => {
+        CreateOutcome::AlreadyJoined(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true)
@@ -53,7 +79,7 @@ pub async fn create(ctx: Context<'_>) -> Result<(), Error> {
             })
             .await?;
         }
-        CreateMatchResult::AlreadyStarted(player_ids) => {
+        CreateOutcome::AlreadyStarted(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true).content(format!(
@@ -67,32 +93,36 @@ pub async fn create(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
-/// Join the pending UNO match in the current channel
+/// Join the UNO match in the current channel, even if it has already started
 #[poise::command(prefix_command, slash_command)]
 pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
-    let join_match_result = join_match(ctx).await;
+    let join_outcome = ctx
+        .data()
+        .matches
+        .join(&ctx.channel_id(), ctx.author().id, ctx.author().tag())
+        .await;
 
-    match join_match_result {
-        JoinMatchResult::DoesNotExist => {
+    match join_outcome {
+        JoinOutcome::DoesNotExist => {
             ctx.send(|m| {
                 m.reply(true)
                     .content("No pending match found in this channel, try `/uno create` first.")
             })
             .await?;
         }
-        JoinMatchResult::AlreadyJoined(player_ids) => {
+        JoinOutcome::AlreadyJoined(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true)
                     .content(
                         format!(
-                            "You are already in the pending match in this channel, wait for it to start. The following users are in the match:\n{players}"
+                            "You are already in the match in this channel. The following users are in the match:\n{players}"
                         )
                     )
             })
             .await?;
         }
-        JoinMatchResult::Joined(player_ids) => {
+        JoinOutcome::Joined(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true)
@@ -104,12 +134,22 @@ pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
             })
             .await?;
         }
-        JoinMatchResult::AlreadyStarted(player_ids) => {
+        JoinOutcome::JoinedOngoing(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
-                m.reply(true).content(format!(
-                    "There is already an ongoing match in this channel with the following users:\n{players}"
-                ))
+                m.reply(true)
+                    .content(
+                        format!(
+                            "You have been dealt in to the ongoing match in this channel. The following users are in the match now:\n{players}"
+                        )
+                    )
+            })
+            .await?;
+        }
+        JoinOutcome::Failed(uno_error) => {
+            ctx.send(|m| {
+                m.reply(true)
+                    .content(format!("Failed to join match: {uno_error}"))
             })
             .await?;
         }
@@ -118,13 +158,52 @@ pub async fn join(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Leave the UNO match in the current channel
+#[poise::command(prefix_command, slash_command)]
+pub async fn leave(ctx: Context<'_>) -> Result<(), Error> {
+    let leave_outcome = ctx
+        .data()
+        .matches
+        .leave(&ctx.channel_id(), ctx.author().id)
+        .await;
+
+    let content = match leave_outcome {
+        LeaveOutcome::DoesNotExist => "There's no match in this channel to leave.".to_string(),
+        LeaveOutcome::NotInMatch => "You are not in the match in this channel.".to_string(),
+        LeaveOutcome::Left => "You have left the match in this channel.".to_string(),
+        LeaveOutcome::LeftAsHost(Some(new_host)) => {
+            format!("You have left the match in this channel, <@{new_host}> is the host now.")
+        }
+        LeaveOutcome::LeftAsHost(None) => {
+            "You have left the match in this channel. Nobody else was in it, so it was removed."
+                .to_string()
+        }
+    };
+
+    ctx.send(|m| m.reply(true).content(content)).await?;
+
+    Ok(())
+}
+
 /// Start the pending UNO match in the current channel
 #[poise::command(prefix_command, slash_command)]
-pub async fn start(ctx: Context<'_>) -> Result<(), Error> {
-    let start_match_result = start_match(ctx).await;
+pub async fn start(
+    ctx: Context<'_>,
+    #[description = "Seat the players randomly instead of in the order they joined"]
+    shuffle_seats: Option<bool>,
+) -> Result<(), Error> {
+    let start_outcome = ctx
+        .data()
+        .matches
+        .start(
+            &ctx.channel_id(),
+            ctx.author().id,
+            shuffle_seats.unwrap_or(false),
+        )
+        .await;
 
-    match start_match_result {
-        StartMatchResult::AlreadyStarted(player_ids) => {
+    match start_outcome {
+        StartOutcome::AlreadyStarted(player_ids) => {
             let players = join_player_list_to_string(player_ids.iter());
             ctx.send(|m| {
                 m.reply(true).content(format!(
@@ -133,7 +212,7 @@ pub async fn start(ctx: Context<'_>) -> Result<(), Error> {
             })
             .await?;
         }
-        StartMatchResult::DoesNotExist => {
+        StartOutcome::DoesNotExist => {
             ctx.send(|m| {
                 m.reply(true).content(
                     "There's no pending match in this channel. Use `/uno create` to create one.",
@@ -141,22 +220,27 @@ pub async fn start(ctx: Context<'_>) -> Result<(), Error> {
             })
             .await?;
         }
-        StartMatchResult::NotHost => {
+        StartOutcome::NotHost => {
             ctx.send(|m| {
                 m.reply(true)
                     .content("A pending match can only be started by the host.")
             })
             .await?;
         }
-        StartMatchResult::Started => {
+        StartOutcome::Started => {
+            let snapshot = ctx
+                .data()
+                .matches
+                .player_snapshot(&ctx.channel_id(), ctx.author().id)
+                .await?;
             ctx.send(|m| {
                 m.reply(true)
                     .content("Match started! Nothing else will happen yet tho...or will it?")
-                    .components(|c| UnoButton::create_components(c))
+                    .components(|c| UnoButton::create_components(c, &snapshot))
             })
             .await?;
         }
-        StartMatchResult::UnoError(uno_error) => {
+        StartOutcome::Failed(uno_error) => {
             ctx.send(|m| {
                 m.reply(true)
                     .content(format!("Failed to start match: {uno_error}"))
@@ -168,6 +252,111 @@ pub async fn start(ctx: Context<'_>) -> Result<(), Error> {
     Ok(())
 }
 
+/// Pick your team for the pending UNO match, which makes it a team match
+#[poise::command(prefix_command, slash_command)]
+pub async fn team(
+    ctx: Context<'_>,
+    #[description = "The team to play in, partners sit opposite each other"]
+    #[min = 1]
+    team: u8,
+) -> Result<(), Error> {
+    // Prefix commands don't enforce the minimum
+    let team = team.max(1);
+    let team_outcome = ctx
+        .data()
+        .matches
+        .pick_team(&ctx.channel_id(), ctx.author().id, usize::from(team - 1))
+        .await;
+
+    let content = match team_outcome {
+        TeamOutcome::AlreadyStarted => {
+            "The match has already started, teams can only be picked before that.".to_string()
+        }
+        TeamOutcome::DoesNotExist => {
+            "There's no pending match in this channel. Use `/uno create` to create one.".to_string()
+        }
+        TeamOutcome::NotInMatch => "Join the match with `/uno join` first.".to_string(),
+        TeamOutcome::Picked(teams) => {
+            let teams = teams
+                .iter()
+                .map(|(team, player_ids)| {
+                    let players = player_ids
+                        .iter()
+                        .map(|id| format!("<@{id}>"))
+                        .collect::<Vec<_>>()
+                        .join(", ");
+                    format!("Team {}: {players}", team + 1)
+                })
+                .collect::<Vec<_>>()
+                .join("\n");
+            format!("You are in team {team} now. The teams so far:\n{teams}")
+        }
+    };
+
+    ctx.send(|m| m.reply(true).content(content)).await?;
+
+    Ok(())
+}
+
+/// Watch the ongoing UNO match in the current channel
+#[poise::command(prefix_command, slash_command)]
+pub async fn spectate(ctx: Context<'_>) -> Result<(), Error> {
+    let Some(mut view) = start_spectating(ctx.data(), ctx.channel_id(), ctx.author().id).await else {
+        ctx.send(|m| {
+            m.reply(true)
+                .ephemeral(true)
+                .content("There's no ongoing match to spectate in this channel.")
+        })
+        .await?;
+        return Ok(());
+    };
+
+    let reply_handle = ctx
+        .send(|m| m.reply(true).ephemeral(true).content(&view))
+        .await?;
+
+    let started_at = Instant::now();
+    while let Some(new_view) = wait_for_view_change(
+        ctx.data(),
+        ctx.channel_id(),
+        ctx.author().id,
+        &view,
+        started_at,
+    )
+    .await
+    {
+        reply_handle.edit(ctx, |m| m.content(&new_view)).await?;
+        view = new_view;
+    }
+
+    stop_spectating(ctx.data(), ctx.channel_id(), ctx.author().id).await;
+
+    Ok(())
+}
+
+/// Toggle whether spectators can see every player's hand
+#[poise::command(prefix_command, slash_command)]
+pub async fn open_hands(ctx: Context<'_>) -> Result<(), Error> {
+    let open_hands_outcome = ctx
+        .data()
+        .matches
+        .toggle_open_hands(&ctx.channel_id(), ctx.author().id)
+        .await;
+
+    let content = match open_hands_outcome {
+        OpenHandsOutcome::DoesNotExist => {
+            "There's no match in this channel. Use `/uno create` to create one."
+        }
+        OpenHandsOutcome::NotHost => "Open hands can only be toggled by the host.",
+        OpenHandsOutcome::Toggled(true) => "Spectators can now see every player's hand.",
+        OpenHandsOutcome::Toggled(false) => "Spectators can no longer see the players' hands.",
+    };
+
+    ctx.send(|m| m.reply(true).content(content)).await?;
+
+    Ok(())
+}
+
 pub trait AsEmoji {
     fn as_emoji(&self) -> ReactionType;
 }
@@ -192,106 +381,6 @@ impl AsEmoji for Card {
     }
 }
 
-enum CreateMatchResult {
-    AlreadyExists(HashSet<UserId>),
-    AlreadyJoined(HashSet<UserId>),
-    AlreadyStarted(HashSet<UserId>),
-    Created,
-}
-
-enum JoinMatchResult {
-    AlreadyJoined(HashSet<UserId>),
-    AlreadyStarted(HashSet<UserId>),
-    DoesNotExist,
-    Joined(HashSet<UserId>),
-}
-
-enum StartMatchResult {
-    AlreadyStarted(HashSet<UserId>),
-    DoesNotExist,
-    NotHost,
-    Started,
-    UnoError(UnoError),
-}
-
-async fn create_match(ctx: Context<'_>) -> CreateMatchResult {
-    let mut hash_map = ctx.data().matches.lock().await;
-    if let Some(existing_match) = hash_map.get_mut(&ctx.channel_id()) {
-        let player_ids = existing_match.get_player_ids();
-        match existing_match {
-            UnoGame::Pending { .. } => {
-                if player_ids.contains(&ctx.author().id) {
-                    CreateMatchResult::AlreadyJoined(player_ids)
-                } else {
-                    CreateMatchResult::AlreadyExists(player_ids)
-                }
-            }
-            UnoGame::Ongoing { .. } => CreateMatchResult::AlreadyStarted(player_ids),
-        }
-    } else {
-        let mut queued_users = BTreeMap::new();
-        queued_users.insert(ctx.author().id, ctx.author().tag());
-
-        hash_map.insert(
-            ctx.channel_id(),
-            UnoGame::Pending {
-                channel_id: ctx.channel_id(),
-                host: ctx.author().id,
-                queued_users,
-            },
-        );
-        CreateMatchResult::Created
-    }
-}
-
-async fn join_match(ctx: Context<'_>) -> JoinMatchResult {
-    let mut hash_map = ctx.data().matches.lock().await;
-    if let Some(existing_match) = hash_map.get_mut(&ctx.channel_id()) {
-        match existing_match {
-            UnoGame::Pending { queued_users, .. } => {
-                if let std::collections::btree_map::Entry::Vacant(e) =
-                    queued_users.entry(ctx.author().id)
-                {
-                    e.insert(ctx.author().tag());
-                    JoinMatchResult::Joined(existing_match.get_player_ids())
-                } else {
-                    JoinMatchResult::AlreadyJoined(existing_match.get_player_ids())
-                }
-            }
-            UnoGame::Ongoing { .. } => {
-                JoinMatchResult::AlreadyStarted(existing_match.get_player_ids())
-            }
-        }
-    } else {
-        JoinMatchResult::DoesNotExist
-    }
-}
-
-async fn start_match(ctx: Context<'_>) -> StartMatchResult {
-    let mut hash_map = ctx.data().matches.lock().await;
-    if let Some(existing_match) = hash_map.get_mut(&ctx.channel_id()) {
-        match existing_match {
-            UnoGame::Ongoing { .. } => {
-                StartMatchResult::AlreadyStarted(existing_match.get_player_ids())
-            }
-            UnoGame::Pending {
-                host: started_by, ..
-            } => {
-                if started_by != &ctx.author().id {
-                    StartMatchResult::NotHost
-                } else {
-                    match existing_match.into_ongoing() {
-                        Ok(_) => StartMatchResult::Started,
-                        Err(err) => StartMatchResult::UnoError(err),
-                    }
-                }
-            }
-        }
-    } else {
-        StartMatchResult::DoesNotExist
-    }
-}
-
 fn join_player_list_to_string<'a>(player_list_iter: impl Iterator<Item = &'a UserId>) -> String {
     player_list_iter
         .map(|id| format!("<@{id}>"))
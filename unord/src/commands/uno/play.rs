@@ -0,0 +1,258 @@
+use match_service::handle::MatchSnapshot;
+use runo::{
+    card::{Card, CardColor, CardId, DeckCard, PlayedCard},
+    notation::Notation,
+    turn::{PlayAction, TurnAction, TurnActionResult},
+};
+use strum::IntoEnumIterator;
+
+use crate::{Context, Error};
+
+use super::error::UnoCommandError;
+
+// Discord shows at most 25 autocomplete choices
+const MAX_AUTOCOMPLETE_CHOICES: usize = 25;
+
+/// Parses a color by its name or first letter, ignoring case.
+fn parse_color(input: &str) -> Option<CardColor> {
+    let input = input.trim().to_lowercase();
+    CardColor::iter().find(|color| {
+        let name = color.to_string().to_lowercase();
+        name == input || name[..1] == input
+    })
+}
+
+/// Finds the card the user meant in their hand. `input` is either the id of a card, which is what
+/// the autocomplete fills in, or the card in notation like `r5`. Wild cards may come with their
+/// color, like `w+4:r`, which is returned as well.
+fn find_card<'a>(hand: &'a [DeckCard], input: &str) -> Option<(&'a DeckCard, Option<CardColor>)> {
+    if let Ok(card_id) = input.trim().parse::<CardId>() {
+        if let Some(card) = hand.iter().find(|card| card.id == card_id) {
+            return Some((card, None));
+        }
+    }
+
+    let (card, chosen_color) = match input.parse::<PlayedCard>() {
+        Ok(PlayedCard::Wild(color)) => (Card::Wild, Some(color)),
+        Ok(PlayedCard::WildDraw(color)) => (Card::WildDraw, Some(color)),
+        _ => (input.parse().ok()?, None),
+    };
+    let card = hand.iter().find(|x| x.card == card)?;
+    Some((card, chosen_color))
+}
+
+/// Lists the distinct cards in the hand that contain `partial`, the ones that can be played right
+/// now first.
+fn hand_choices<'a>(
+    snapshot: &'a MatchSnapshot,
+    player_id: u64,
+    partial: &str,
+) -> Vec<&'a DeckCard> {
+    let partial = partial.trim().to_lowercase();
+
+    let mut choices: Vec<&DeckCard> = Vec::new();
+    for card in snapshot.hand(player_id).unwrap_or_default() {
+        let matches_partial = card.to_string().to_lowercase().contains(&partial)
+            || card.card.notation().to_lowercase().starts_with(&partial);
+        if matches_partial && !choices.iter().any(|x| x.card == card.card) {
+            choices.push(card);
+        }
+    }
+    choices.sort_by_key(|card| !card.card.can_be_played_on(&snapshot.last_played_card));
+    choices.truncate(MAX_AUTOCOMPLETE_CHOICES);
+
+    choices
+}
+
+async fn autocomplete_card(
+    ctx: Context<'_>,
+    partial: &str,
+) -> Vec<poise::AutocompleteChoice<String>> {
+    let user_id = ctx.author().id;
+    let Ok(snapshot) = ctx
+        .data()
+        .matches
+        .player_snapshot(&ctx.channel_id(), user_id)
+        .await
+    else {
+        return Vec::new();
+    };
+
+    hand_choices(&snapshot, user_id.0, partial)
+        .into_iter()
+        .map(|card| poise::AutocompleteChoice {
+            name: if card.card.can_be_played_on(&snapshot.last_played_card) {
+                card.to_string()
+            } else {
+                format!("{card} (not playable)")
+            },
+            value: card.id.to_string(),
+        })
+        .collect()
+}
+
+async fn autocomplete_color(_ctx: Context<'_>, partial: &str) -> Vec<String> {
+    let partial = partial.trim().to_lowercase();
+    CardColor::iter()
+        .map(|color| color.to_string())
+        .filter(|color| color.to_lowercase().starts_with(&partial))
+        .collect()
+}
+
+/// Play a card from your hand, e.g. `r5`, `gs`, `y+2`, `w+4:r` or `w+4 red`
+#[poise::command(prefix_command, slash_command)]
+pub async fn play(
+    ctx: Context<'_>,
+    #[description = "The card to play"]
+    #[autocomplete = "autocomplete_card"]
+    card: String,
+    #[description = "The color to choose, only needed for wild cards"]
+    #[autocomplete = "autocomplete_color"]
+    color: Option<String>,
+    #[description = "Say UNO along with your second-to-last card"] uno: Option<bool>,
+) -> Result<(), Error> {
+    let user_id = ctx.author().id;
+
+    let snapshot = match ctx
+        .data()
+        .matches
+        .player_snapshot(&ctx.channel_id(), user_id)
+        .await
+    {
+        Ok(snapshot) => snapshot,
+        Err(err) => {
+            let content = UnoCommandError::from(err).user_message();
+            ctx.send(|m| m.reply(true).ephemeral(true).content(content))
+                .await?;
+            return Ok(());
+        }
+    };
+
+    let hand = snapshot.hand(user_id.0).unwrap_or_default();
+    let Some((chosen_card, card_color)) = find_card(hand, &card) else {
+        ctx.send(|m| {
+            m.reply(true)
+                .ephemeral(true)
+                .content(format!("You don't have a card like `{card}` in your hand."))
+        })
+        .await?;
+        return Ok(());
+    };
+
+    // The color of a starting wild card is not chosen yet, the match tells the player about that
+    let top_card = &snapshot.last_played_card;
+    if !snapshot.starting_color_pending && !chosen_card.card.can_be_played_on(top_card) {
+        ctx.send(|m| {
+            m.reply(true).ephemeral(true).content(format!(
+                "You can't play `{}` on `{}`, it has to match its color or symbol.",
+                chosen_card.card.notation(),
+                top_card.notation()
+            ))
+        })
+        .await?;
+        return Ok(());
+    }
+
+    let color = color.as_deref().map(parse_color).or(card_color.map(Some));
+    let chosen_color = match (&chosen_card.card, color) {
+        (Card::Colored(_, _), _) => None,
+        (Card::Wild | Card::WildDraw, Some(Some(color))) => Some(color),
+        (Card::Wild | Card::WildDraw, Some(None) | None) => {
+            ctx.send(|m| {
+                m.reply(true)
+                    .ephemeral(true)
+                    .content("Please choose a color for the wild card, e.g. `w+4 red`.")
+            })
+            .await?;
+            return Ok(());
+        }
+    };
+
+    let turn_action = TurnAction::Play(PlayAction::Card {
+        card_id: chosen_card.id,
+        chosen_color,
+        uno: uno.unwrap_or(false),
+    });
+    let content = match ctx
+        .data()
+        .matches
+        .play_turn(&ctx.channel_id(), user_id, turn_action)
+        .await
+    {
+        Ok(result) if result.turn_action_result == TurnActionResult::ChooseHandSwap => {
+            format!("You played {chosen_card}, press Swap Hands to choose whom to swap hands with.")
+        }
+        Ok(result) => format!("You played {chosen_card}, result: {:?}", result),
+        Err(err) => UnoCommandError::from(err).user_message(),
+    };
+
+    ctx.send(|m| m.reply(true).ephemeral(true).content(content))
+        .await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::uno::test_util::{create_snapshot, deck_cards};
+
+    #[test]
+    fn parse_color_by_name_or_first_letter() {
+        assert_eq!(parse_color("red"), Some(CardColor::Red));
+        assert_eq!(parse_color(" Blue "), Some(CardColor::Blue));
+        assert_eq!(parse_color("G"), Some(CardColor::Green));
+        assert_eq!(parse_color("y"), Some(CardColor::Yellow));
+        assert_eq!(parse_color("purple"), None);
+        assert_eq!(parse_color(""), None);
+    }
+
+    #[test]
+    fn find_card_by_id_before_notation() {
+        let hand = deck_cards(&["r5", "g2", "b7"]);
+
+        assert_eq!(find_card(&hand, "2"), Some((&hand[2], None)));
+        assert_eq!(find_card(&hand, "g2"), Some((&hand[1], None)));
+        assert_eq!(find_card(&hand, "R5"), Some((&hand[0], None)));
+        // Neither an id in the hand nor a card in it
+        assert_eq!(find_card(&hand, "9"), None);
+        assert_eq!(find_card(&hand, "y1"), None);
+    }
+
+    #[test]
+    fn find_card_with_chosen_color() {
+        let hand = deck_cards(&["r5", "w+4", "w"]);
+
+        assert_eq!(
+            find_card(&hand, "w+4:r"),
+            Some((&hand[1], Some(CardColor::Red)))
+        );
+        assert_eq!(
+            find_card(&hand, "W:b"),
+            Some((&hand[2], Some(CardColor::Blue)))
+        );
+        assert_eq!(find_card(&hand, "w+4"), Some((&hand[1], None)));
+        // Colored cards have no color to choose
+        assert_eq!(find_card(&hand, "r5:g"), None);
+    }
+
+    #[test]
+    fn hand_choices_fold_duplicates_and_list_playable_first() {
+        let hand = deck_cards(&["g2", "r5", "b7", "r5", "w"]);
+        let snapshot = create_snapshot(vec![(1, hand.clone()), (2, Vec::new())], "r1");
+
+        let choices = hand_choices(&snapshot, 1, "");
+        assert_eq!(choices, vec![&hand[1], &hand[4], &hand[0], &hand[2]]);
+    }
+
+    #[test]
+    fn hand_choices_match_name_or_notation() {
+        let hand = deck_cards(&["g2", "r5", "y+2", "w+4"]);
+        let snapshot = create_snapshot(vec![(1, hand.clone()), (2, Vec::new())], "r1");
+
+        assert_eq!(hand_choices(&snapshot, 1, "green"), vec![&hand[0]]);
+        assert_eq!(hand_choices(&snapshot, 1, " Y+"), vec![&hand[2]]);
+        assert_eq!(hand_choices(&snapshot, 1, "wild"), vec![&hand[3]]);
+        assert!(hand_choices(&snapshot, 2, "").is_empty());
+    }
+}
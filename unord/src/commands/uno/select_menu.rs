@@ -1,64 +1,168 @@
 use std::{sync::Arc, time::Duration};
 
-use color_eyre::Result;
 use poise::{
     async_trait,
     serenity_prelude::{
-        CollectComponentInteraction, Context, CreateInteractionResponseData,
-        InteractionResponseType, MessageComponentInteraction, ShardMessenger,
+        ButtonStyle, CollectComponentInteraction, Context, CreateInteractionResponseData,
+        CreateSelectMenuOption, InteractionResponseType, MessageComponentInteraction,
+        ShardMessenger,
     },
 };
-use runo::card::{Card, CardColor};
+use runo::card::{CardColor, DeckCard};
 
-use super::AsEmoji;
+use super::{
+    error::{Result, UnoCommandError},
+    AsEmoji,
+};
+
+/// Discord allows at most 25 options per select menu, so bigger menus are split into pages.
+const OPTIONS_PER_PAGE: usize = 25;
 
 #[async_trait]
 pub trait SelectMenu<T>: Sync {
     fn custom_id() -> String;
 
+    fn previous_page_custom_id() -> String {
+        format!("{}_previous_page", Self::custom_id())
+    }
+
+    fn next_page_custom_id() -> String {
+        format!("{}_next_page", Self::custom_id())
+    }
+
+    fn content(&self) -> String;
+
+    fn options_count(&self) -> usize;
+
+    /// Fills in the option at `index`. Its value has to be the index, so that `on_collected` can
+    /// find the option again no matter which page it was on.
+    fn create_option<'a>(
+        &self,
+        index: usize,
+        o: &'a mut CreateSelectMenuOption,
+    ) -> &'a mut CreateSelectMenuOption;
+
+    fn page_count(&self) -> usize {
+        self.options_count().div_ceil(OPTIONS_PER_PAGE).max(1)
+    }
+
     fn create_interaction_response_data<'a>(
         &self,
         _ctx: &Context,
-        _ird: &'a mut CreateInteractionResponseData,
-    );
+        ird: &'a mut CreateInteractionResponseData,
+        page: usize,
+    ) {
+        let page_count = self.page_count();
+        let first_index = page * OPTIONS_PER_PAGE;
+        let last_index = (first_index + OPTIONS_PER_PAGE).min(self.options_count());
+
+        let content = if page_count > 1 {
+            format!("{} (page {}/{page_count})", self.content(), page + 1)
+        } else {
+            self.content()
+        };
+
+        ird.ephemeral(true).content(content).components(|c| {
+            c.create_action_row(|ar| {
+                ar.create_select_menu(|sm| {
+                    sm.custom_id(Self::custom_id())
+                        .min_values(1)
+                        .max_values(1)
+                        .options(|o| {
+                            for index in first_index..last_index {
+                                o.create_option(|o| self.create_option(index, o));
+                            }
+                            o
+                        })
+                })
+            });
+
+            if page_count > 1 {
+                c.create_action_row(|ar| {
+                    ar.create_button(|b| {
+                        b.custom_id(Self::previous_page_custom_id())
+                            .label("Previous")
+                            .style(ButtonStyle::Secondary)
+                            .disabled(page == 0)
+                    })
+                    .create_button(|b| {
+                        b.custom_id(Self::next_page_custom_id())
+                            .label("Next")
+                            .style(ButtonStyle::Secondary)
+                            .disabled(page + 1 == page_count)
+                    })
+                });
+            }
+
+            c
+        });
+    }
 
     fn create_collect_component_interaction(
         &self,
-        _shard: impl AsRef<ShardMessenger>,
-        _interaction: &MessageComponentInteraction,
-    ) -> CollectComponentInteraction;
+        shard: impl AsRef<ShardMessenger>,
+        interaction: &MessageComponentInteraction,
+    ) -> CollectComponentInteraction {
+        CollectComponentInteraction::new(shard.as_ref())
+            .timeout(Duration::from_secs(60))
+            .author_id(interaction.user.id)
+            .channel_id(interaction.channel_id)
+            .collect_limit(1)
+            .filter(move |component_interaction| {
+                let custom_id = &component_interaction.data.custom_id;
+                *custom_id == Self::custom_id()
+                    || *custom_id == Self::previous_page_custom_id()
+                    || *custom_id == Self::next_page_custom_id()
+            })
+    }
 
+    /// Shows the menu and waits until an option is selected, switching pages in between whenever
+    /// the user asks for it.
     async fn await_selection(
         &mut self,
         ctx: &Context,
         interaction: &MessageComponentInteraction,
-    ) -> Result<Option<Arc<MessageComponentInteraction>>> {
+    ) -> Result<Arc<MessageComponentInteraction>> {
+        let mut page = 0;
+
         interaction
             .create_interaction_response(ctx, |ir| {
                 ir.kind(InteractionResponseType::ChannelMessageWithSource)
                     .interaction_response_data(|ird| {
-                        self.create_interaction_response_data(ctx, ird);
+                        self.create_interaction_response_data(ctx, ird, page);
                         ird
                     })
             })
             .await?;
 
-        let collect_component_interaction =
-            self.create_collect_component_interaction(ctx, interaction);
-        let select_interaction = collect_component_interaction.await;
+        loop {
+            let collect_component_interaction =
+                self.create_collect_component_interaction(ctx, interaction);
+            let Some(component_interaction) = collect_component_interaction.await else {
+                return Err(UnoCommandError::SelectionTimedOut);
+            };
 
-        match select_interaction {
-            Some(select_interaction) => {
-                if select_interaction.data.custom_id != Self::custom_id() {
-                    // TODO: Error
-                    return Ok(Some(select_interaction));
-                }
-
-                self.on_collected(&select_interaction.data.values);
-                return Ok(Some(select_interaction));
+            let custom_id = &component_interaction.data.custom_id;
+            if *custom_id == Self::custom_id() {
+                self.on_collected(&component_interaction.data.values);
+                return Ok(component_interaction);
+            } else if *custom_id == Self::previous_page_custom_id() {
+                page = page.saturating_sub(1);
+            } else if *custom_id == Self::next_page_custom_id() {
+                page = (page + 1).min(self.page_count() - 1);
+            } else {
+                return Err(UnoCommandError::UnexpectedInteraction);
             }
-            // TODO: Error
-            None => Ok(select_interaction),
+
+            component_interaction
+                .create_interaction_response(ctx, |ir| {
+                    ir.kind(InteractionResponseType::UpdateMessage)
+                        .interaction_response_data(|ird| {
+                            self.create_interaction_response_data(ctx, ird, page);
+                            ird
+                        })
+                })
+                .await?;
         }
     }
 
@@ -67,22 +171,25 @@ pub trait SelectMenu<T>: Sync {
     fn get_selection(&self) -> Option<T>;
 }
 
+/// Lists the cards, and with `offer_uno` every card a second time to say UNO along with it.
 pub struct CardSelectMenu<'cards> {
-    available_cards: &'cards [Card],
+    available_cards: &'cards [DeckCard],
+    offer_uno: bool,
     selected_index: Option<usize>,
 }
 
 impl<'cards> CardSelectMenu<'cards> {
-    pub fn new(available_cards: &'cards [Card]) -> Self {
+    pub fn new(available_cards: &'cards [DeckCard], offer_uno: bool) -> Self {
         Self {
             available_cards,
+            offer_uno,
             selected_index: None,
         }
     }
 }
 
 #[async_trait]
-impl<'cards> SelectMenu<&'cards Card> for CardSelectMenu<'cards> {
+impl<'cards> SelectMenu<(&'cards DeckCard, bool)> for CardSelectMenu<'cards> {
     fn custom_id() -> String {
         "select_menu_card".to_string()
     }
@@ -96,52 +203,41 @@ impl<'cards> SelectMenu<&'cards Card> for CardSelectMenu<'cards> {
         }
     }
 
-    fn get_selection(&self) -> Option<&'cards Card> {
-        self.selected_index
-            .and_then(|index| self.available_cards.get(index))
+    /// Returns the card and whether to say UNO along with it.
+    fn get_selection(&self) -> Option<(&'cards DeckCard, bool)> {
+        let index = self.selected_index.filter(|x| *x < self.options_count())?;
+        let cards_count = self.available_cards.len();
+        Some((
+            &self.available_cards[index % cards_count],
+            index >= cards_count,
+        ))
     }
 
-    fn create_interaction_response_data<'a>(
-        &self,
-        _ctx: &Context,
-        ird: &'a mut CreateInteractionResponseData,
-    ) {
-        ird.content("Select the card you want to play:")
-            .components(|c| {
-                c.create_action_row(|ar| {
-                    ar.create_select_menu(|sm| {
-                        sm.custom_id(Self::custom_id())
-                            .min_values(1)
-                            .max_values(1)
-                            .options(|o| {
-                                for (index, card) in self.available_cards.iter().enumerate() {
-                                    o.create_option(|o| {
-                                        o.label(card.to_string())
-                                            .value(index)
-                                            .emoji(card.as_emoji())
-                                    });
-                                }
-                                o
-                            })
-                    })
-                })
-            })
-            .ephemeral(true);
+    fn content(&self) -> String {
+        "Select the card you want to play:".to_string()
     }
 
-    fn create_collect_component_interaction(
+    fn options_count(&self) -> usize {
+        let cards_count = self.available_cards.len();
+        if self.offer_uno {
+            cards_count * 2
+        } else {
+            cards_count
+        }
+    }
+
+    fn create_option<'a>(
         &self,
-        shard: impl AsRef<ShardMessenger>,
-        interaction: &MessageComponentInteraction,
-    ) -> CollectComponentInteraction {
-        CollectComponentInteraction::new(shard.as_ref())
-            .timeout(Duration::from_secs(60))
-            .author_id(interaction.user.id)
-            .channel_id(interaction.channel_id)
-            .collect_limit(1)
-            .filter(move |select_menu_interaction| {
-                select_menu_interaction.data.custom_id == Self::custom_id()
-            })
+        index: usize,
+        o: &'a mut CreateSelectMenuOption,
+    ) -> &'a mut CreateSelectMenuOption {
+        let card = &self.available_cards[index % self.available_cards.len()];
+        let label = if index >= self.available_cards.len() {
+            format!("{card} and say UNO")
+        } else {
+            card.to_string()
+        };
+        o.label(label).value(index).emoji(card.card.as_emoji())
     }
 }
 
@@ -179,46 +275,75 @@ impl<'cards> SelectMenu<&'cards CardColor> for ColorSelectMenu<'cards> {
             .and_then(|index| self.available_colors.get(index))
     }
 
-    fn create_interaction_response_data<'a>(
+    fn content(&self) -> String {
+        "Select the color you want to set the wild card to:".to_string()
+    }
+
+    fn options_count(&self) -> usize {
+        self.available_colors.len()
+    }
+
+    fn create_option<'a>(
         &self,
-        _ctx: &Context,
-        ird: &'a mut CreateInteractionResponseData,
-    ) {
-        ird.ephemeral(true)
-            .content("Select the color you want to set the wild card to:")
-            .components(|c| {
-                c.create_action_row(|ar| {
-                    ar.create_select_menu(|sm| {
-                        sm.custom_id(Self::custom_id())
-                            .min_values(1)
-                            .max_values(1)
-                            .options(|o| {
-                                for (index, color) in self.available_colors.iter().enumerate() {
-                                    o.create_option(|o| {
-                                        o.label(color.to_string())
-                                            .value(index)
-                                            .emoji(color.as_emoji())
-                                    });
-                                }
-                                o
-                            })
-                    })
-                })
-            });
+        index: usize,
+        o: &'a mut CreateSelectMenuOption,
+    ) -> &'a mut CreateSelectMenuOption {
+        let color = &self.available_colors[index];
+        o.label(color.to_string())
+            .value(index)
+            .emoji(color.as_emoji())
     }
+}
 
-    fn create_collect_component_interaction(
+/// Lists the players by id and name.
+pub struct PlayerSelectMenu<'players> {
+    available_players: &'players [(u64, String)],
+    selected_index: Option<usize>,
+}
+
+impl<'players> PlayerSelectMenu<'players> {
+    pub fn new(available_players: &'players [(u64, String)]) -> Self {
+        Self {
+            available_players,
+            selected_index: None,
+        }
+    }
+}
+
+#[async_trait]
+impl<'players> SelectMenu<&'players (u64, String)> for PlayerSelectMenu<'players> {
+    fn custom_id() -> String {
+        "select_menu_player".to_string()
+    }
+
+    fn on_collected(&mut self, selections: &[String]) {
+        if let Some(selected_index) = selections
+            .iter()
+            .find_map(|str_selection| str_selection.parse::<usize>().ok())
+        {
+            self.selected_index = Some(selected_index);
+        }
+    }
+
+    fn get_selection(&self) -> Option<&'players (u64, String)> {
+        self.selected_index
+            .and_then(|index| self.available_players.get(index))
+    }
+
+    fn content(&self) -> String {
+        "Select the player you want to swap hands with:".to_string()
+    }
+
+    fn options_count(&self) -> usize {
+        self.available_players.len()
+    }
+
+    fn create_option<'a>(
         &self,
-        shard: impl AsRef<ShardMessenger>,
-        interaction: &MessageComponentInteraction,
-    ) -> CollectComponentInteraction {
-        CollectComponentInteraction::new(shard.as_ref())
-            .timeout(Duration::from_secs(60))
-            .author_id(interaction.user.id)
-            .channel_id(interaction.channel_id)
-            .collect_limit(1)
-            .filter(move |select_menu_interaction| {
-                select_menu_interaction.data.custom_id == Self::custom_id()
-            })
+        index: usize,
+        o: &'a mut CreateSelectMenuOption,
+    ) -> &'a mut CreateSelectMenuOption {
+        let (_, name) = &self.available_players[index];
+        o.label(name).value(index)
     }
 }
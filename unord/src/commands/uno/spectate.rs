@@ -0,0 +1,376 @@
+use std::{
+    fmt::Write as _,
+    time::{Duration, Instant},
+};
+
+use match_service::{handle::MatchEvent, service::SpectatorView};
+use poise::serenity_prelude::{
+    ChannelId, Context, InteractionResponseType, MessageComponentInteraction, UserId,
+};
+use runo::{
+    rules::StartingPlayer,
+    turn::{PlayAction, TurnAction, TurnActionResult},
+    uno::StartingCardEffect,
+};
+
+use crate::Data;
+
+use super::error::Result;
+
+const REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+// Interaction tokens expire after 15 minutes, so we stop refreshing a bit before that.
+pub(super) const REFRESH_DURATION: Duration = Duration::from_secs(14 * 60);
+const SHOWN_LOG_ENTRIES: usize = 10;
+const MAX_MESSAGE_LENGTH: usize = 2000;
+
+/// Registers the user as a spectator and returns the initial view, or `None` if there is no
+/// ongoing match in the channel.
+pub async fn start_spectating(
+    data: &Data,
+    channel_id: ChannelId,
+    user_id: UserId,
+) -> Option<String> {
+    let view = data.matches.start_spectating(&channel_id, user_id).await?;
+    Some(render_view(&view))
+}
+
+pub async fn stop_spectating(data: &Data, channel_id: ChannelId, user_id: UserId) {
+    data.matches.stop_spectating(&channel_id, user_id).await;
+}
+
+/// Waits until the public state of the match differs from `current_view` and returns the new
+/// view. Returns `None` once the match is gone or the view has been refreshed for too long.
+pub async fn wait_for_view_change(
+    data: &Data,
+    channel_id: ChannelId,
+    user_id: UserId,
+    current_view: &str,
+    started_at: Instant,
+) -> Option<String> {
+    loop {
+        tokio::time::sleep(REFRESH_INTERVAL).await;
+
+        if started_at.elapsed() >= REFRESH_DURATION {
+            return None;
+        }
+
+        let view = render_view(&data.matches.spectator_view(&channel_id, user_id).await?);
+
+        if view != current_view {
+            return Some(view);
+        }
+    }
+}
+
+pub async fn handle_spectate(
+    ctx: &Context,
+    interaction: &MessageComponentInteraction,
+    data: &Data,
+) -> Result<()> {
+    let Some(mut view) = start_spectating(data, interaction.channel_id, interaction.user.id).await else {
+        interaction
+            .create_interaction_response(ctx, |ir| {
+                ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                    .interaction_response_data(|ird| {
+                        ird.ephemeral(true)
+                            .content("There's no ongoing match to spectate in this channel.")
+                    })
+            })
+            .await?;
+        return Ok(());
+    };
+
+    interaction
+        .create_interaction_response(ctx, |ir| {
+            ir.kind(InteractionResponseType::ChannelMessageWithSource)
+                .interaction_response_data(|ird| ird.ephemeral(true).content(&view))
+        })
+        .await?;
+
+    let started_at = Instant::now();
+    while let Some(new_view) = wait_for_view_change(
+        data,
+        interaction.channel_id,
+        interaction.user.id,
+        &view,
+        started_at,
+    )
+    .await
+    {
+        let edit_result = interaction
+            .edit_original_interaction_response(ctx, |r| r.content(&new_view))
+            .await;
+        if let Err(err) = edit_result {
+            stop_spectating(data, interaction.channel_id, interaction.user.id).await;
+            return Err(err.into());
+        }
+        view = new_view;
+    }
+
+    stop_spectating(data, interaction.channel_id, interaction.user.id).await;
+
+    Ok(())
+}
+
+fn render_view(spectator_view: &SpectatorView) -> String {
+    let SpectatorView {
+        snapshot,
+        spectators_count,
+        open_hands,
+    } = spectator_view;
+    let mut view = String::new();
+
+    let _ = writeln!(
+        view,
+        "**Spectating UNO**{}",
+        if *open_hands { " (open hands)" } else { "" }
+    );
+    if snapshot.starting_color_pending {
+        let _ = writeln!(view, "Top card: Wild, its color is yet to be chosen");
+    } else {
+        let _ = writeln!(view, "Top card: {}", snapshot.last_played_card);
+    }
+    let _ = writeln!(
+        view,
+        "Current turn: <@{}>, next up: <@{}>",
+        snapshot.current_turn_player_id, snapshot.next_turn_player_id
+    );
+
+    let _ = writeln!(view, "\n**Players**");
+    for (player_id, hand) in &snapshot.hands {
+        let _ = write!(view, "<@{player_id}>: {} cards", hand.len());
+        if *open_hands {
+            let hand = hand
+                .iter()
+                .map(|x| x.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            let _ = write!(view, " ({hand})");
+        }
+        view.push('\n');
+    }
+
+    if !snapshot.winner_ids.is_empty() {
+        let winners = snapshot
+            .winner_ids
+            .iter()
+            .map(|id| format!("<@{id}>"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = writeln!(view, "Winners: {winners}");
+    }
+
+    for (team, player_ids) in snapshot.teams.iter().enumerate() {
+        let players = player_ids
+            .iter()
+            .map(|id| format!("<@{id}>"))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = write!(view, "Team {}: {players}", team + 1);
+        if snapshot.winning_team == Some(team) {
+            // The team scores the cards its opponents are left with
+            let score = snapshot
+                .hands
+                .iter()
+                .filter(|(id, _)| !player_ids.contains(id))
+                .flat_map(|(_, hand)| hand)
+                .map(|x| x.card.points())
+                .sum::<u32>();
+            let _ = write!(view, " (won with {score} points)");
+        }
+        view.push('\n');
+    }
+
+    let _ = writeln!(view, "Spectators: {spectators_count}");
+
+    if !snapshot.log.is_empty() {
+        let _ = writeln!(view, "\n**Recent actions**");
+        let log = &snapshot.log[snapshot.log.len().saturating_sub(SHOWN_LOG_ENTRIES)..];
+        for event in log {
+            write_event(&mut view, event);
+        }
+    }
+
+    if view.len() > MAX_MESSAGE_LENGTH {
+        let mut end = MAX_MESSAGE_LENGTH - 1;
+        while !view.is_char_boundary(end) {
+            end -= 1;
+        }
+        view.truncate(end);
+        view.push('…');
+    }
+
+    view
+}
+
+fn write_event(view: &mut String, event: &MatchEvent) {
+    match event {
+        MatchEvent::TurnPlayed {
+            player_id,
+            action,
+            played_card,
+            result,
+        } => {
+            let action = match (action, played_card) {
+                (TurnAction::Play(PlayAction::Card { uno: true, .. }), Some(card)) => {
+                    format!("played {card} and said UNO")
+                }
+                (TurnAction::Play(_), Some(card)) => format!("played {card}"),
+                (TurnAction::Play(_), None) => return,
+                (TurnAction::Draw, Some(card)) => format!("drew cards and played {card}"),
+                (TurnAction::Draw, None) => "drew cards".to_string(),
+                (TurnAction::Uno, _) => "said UNO".to_string(),
+                (TurnAction::Callout, _) => "tried a callout".to_string(),
+                (TurnAction::Challenge, _) => "challenged the Wild Draw (+4)".to_string(),
+                (TurnAction::SwapHands(target_id), _) => {
+                    format!("swapped hands with <@{target_id}>")
+                }
+                (TurnAction::ChooseColor(color), _) => format!("chose {color} to start with"),
+            };
+            let outcome = match &result.turn_action_result {
+                TurnActionResult::CalledOut(player_ids) => format!(
+                    " and caught {}",
+                    player_ids
+                        .iter()
+                        .map(|id| format!("<@{id}>"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+                TurnActionResult::CalloutFailed => " but nobody could be called out".to_string(),
+                TurnActionResult::UnoFailed => " with more than one card left".to_string(),
+                TurnActionResult::WildDrawAccepted => " for the Wild Draw (+4)".to_string(),
+                TurnActionResult::ChallengeSucceeded => " and caught a bluff".to_string(),
+                TurnActionResult::ChallengeFailed => " but it was played fairly".to_string(),
+                TurnActionResult::ChooseHandSwap => {
+                    " and is choosing whom to swap hands with".to_string()
+                }
+                TurnActionResult::HandsRotated => " and everyone passed their hand on".to_string(),
+                TurnActionResult::DrewCards(count) | TurnActionResult::DrewAndPlayed(count, _) => {
+                    format!(", {count} in total")
+                }
+                _ => String::new(),
+            };
+
+            let _ = writeln!(view, "<@{player_id}> {action}{outcome}");
+            if result.won {
+                let _ = writeln!(view, "<@{player_id}> has no cards left and won!");
+            }
+        }
+        MatchEvent::StartingPlayer(choice) => {
+            let reason = match choice.policy {
+                StartingPlayer::Random => "picked at random",
+                StartingPlayer::Host => "as the host",
+                StartingPlayer::LeftOfDealer => "as the player after the dealer",
+                StartingPlayer::HighestCard => "with the highest card drawn",
+            };
+            let _ = writeln!(view, "<@{}> goes first, {reason}", choice.player_id);
+        }
+        MatchEvent::StartingCard { card, effect } => {
+            let _ = match effect {
+                StartingCardEffect::None => writeln!(view, "The match started with {card}"),
+                StartingCardEffect::Skip { player_id } => writeln!(
+                    view,
+                    "The match started with {card}, so <@{player_id}> was skipped"
+                ),
+                StartingCardEffect::Reverse => writeln!(
+                    view,
+                    "The match started with {card}, so play goes the other way"
+                ),
+                StartingCardEffect::Draw { player_id } => writeln!(
+                    view,
+                    "The match started with {card}, so <@{player_id}> drew two cards and was skipped"
+                ),
+                StartingCardEffect::Wild { player_id } => writeln!(
+                    view,
+                    "The match started with a Wild, <@{player_id}> chooses its color"
+                ),
+            };
+        }
+        MatchEvent::PlayerJoined { player_id } => {
+            let _ = writeln!(view, "<@{player_id}> joined the match");
+        }
+        MatchEvent::PlayerLeft { player_id } => {
+            let _ = writeln!(view, "<@{player_id}> left the match");
+        }
+        MatchEvent::TurnTimedOut { player_id } => {
+            let _ = writeln!(view, "<@{player_id}> took too long and was skipped");
+        }
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use runo::{
+        card::{CardColor, ColoredCard, PlayedCard},
+        uno::PlayTurnResult,
+    };
+
+    use super::*;
+    use crate::commands::uno::test_util::{create_snapshot, deck_cards};
+
+    fn create_view(open_hands: bool) -> SpectatorView {
+        let snapshot = create_snapshot(
+            vec![(1, deck_cards(&["r5", "gs"])), (2, deck_cards(&["w"]))],
+            "r1",
+        );
+        SpectatorView {
+            snapshot,
+            spectators_count: 3,
+            open_hands,
+        }
+    }
+
+    #[test]
+    fn render_view_without_open_hands() {
+        let view = render_view(&create_view(false));
+
+        assert!(view.starts_with("**Spectating UNO**\n"));
+        assert!(view.contains("Top card: Red 1\n"));
+        assert!(view.contains("Current turn: <@1>, next up: <@2>\n"));
+        assert!(view.contains("<@1>: 2 cards\n"));
+        assert!(view.contains("<@2>: 1 cards\n"));
+        assert!(view.contains("Spectators: 3\n"));
+        assert!(!view.contains("Red 5"));
+    }
+
+    #[test]
+    fn render_view_with_open_hands() {
+        let view = render_view(&create_view(true));
+
+        assert!(view.starts_with("**Spectating UNO** (open hands)\n"));
+        assert!(view.contains("<@1>: 2 cards (Red 5, Green Skip)\n"));
+        assert!(view.contains("<@2>: 1 cards (Wild)\n"));
+    }
+
+    #[test]
+    fn render_view_shows_played_number_cards() {
+        let mut spectator_view = create_view(false);
+        spectator_view.snapshot.log.push(MatchEvent::TurnPlayed {
+            player_id: 2,
+            action: TurnAction::Play(PlayAction::Card {
+                card_id: 0,
+                chosen_color: None,
+                uno: true,
+            }),
+            played_card: Some(PlayedCard::Colored(CardColor::Red, ColoredCard::Number(7))),
+            result: PlayTurnResult {
+                turn_action_result: TurnActionResult::Neutral,
+                won: false,
+            },
+        });
+
+        let view = render_view(&spectator_view);
+        assert!(view.contains("<@2> played Red 7 and said UNO\n"));
+    }
+
+    #[test]
+    fn render_view_is_cut_to_message_length() {
+        let mut spectator_view = create_view(true);
+        spectator_view.snapshot.hands[0].1 = deck_cards(&["w+4"; 200]);
+
+        let view = render_view(&spectator_view);
+        assert_eq!(view.len(), MAX_MESSAGE_LENGTH - 1 + '…'.len_utf8());
+        assert!(view.ends_with('…'));
+    }
+}
@@ -0,0 +1,40 @@
+use match_service::handle::MatchSnapshot;
+use runo::{
+    card::{DeckCard, PlayedCard},
+    rules::Rules,
+};
+
+/// Parses the cards from their notation, numbering them in order.
+pub fn deck_cards(notations: &[&str]) -> Vec<DeckCard> {
+    notations
+        .iter()
+        .enumerate()
+        .map(|(id, notation)| DeckCard {
+            id: id as u32,
+            card: notation
+                .parse()
+                .expect("Test cards must be valid notation."),
+        })
+        .collect()
+}
+
+/// A match with the given hands and top card in which it is the first player's turn.
+pub fn create_snapshot(hands: Vec<(u64, Vec<DeckCard>)>, last_played_card: &str) -> MatchSnapshot {
+    MatchSnapshot {
+        current_turn_player_id: hands[0].0,
+        next_turn_player_id: hands[1 % hands.len()].0,
+        last_played_card: last_played_card
+            .parse::<PlayedCard>()
+            .expect("The top card must be valid notation."),
+        hands,
+        names: Vec::new(),
+        winner_ids: Vec::new(),
+        pending_wild_draw: None,
+        pending_hand_swap: None,
+        starting_color_pending: false,
+        teams: Vec::new(),
+        winning_team: None,
+        rules: Rules::default(),
+        log: Vec::new(),
+    }
+}
@@ -1,73 +1,18 @@
 mod commands;
 use commands::{uno::button::UnoButton, *};
 
+use match_service::{handle::MatchOptions, service::MatchService};
 use poise::serenity_prelude::{self as serenity, ChannelId, UserId};
-use runo::{error::UnoError, uno::Uno};
-use std::{
-    collections::{BTreeMap, HashMap, HashSet},
-    env::var,
-};
-use tokio::sync::Mutex;
+use std::env::var;
+use tracing::{error, info};
 
 // Types used by all command functions
 type Error = Box<dyn std::error::Error + Send + Sync>;
 type Context<'a> = poise::Context<'a, Data, Error>;
 
-pub enum UnoGame {
-    Pending {
-        channel_id: ChannelId,
-        host: UserId,
-        queued_users: BTreeMap<UserId, String>,
-    },
-    Ongoing {
-        channel_id: ChannelId,
-        host: UserId,
-        game: Uno,
-    },
-}
-
-impl UnoGame {
-    pub fn get_player_ids(&self) -> HashSet<UserId> {
-        match self {
-            UnoGame::Pending { queued_users, .. } => queued_users.keys().cloned().collect(),
-            UnoGame::Ongoing { game, .. } => {
-                game.get_player_ids().into_iter().map(UserId).collect()
-            }
-        }
-    }
-
-    pub fn into_ongoing(&mut self) -> Result<(), UnoError> {
-        match self {
-            UnoGame::Pending {
-                channel_id,
-                queued_users,
-                host,
-            } => {
-                match Uno::new_with_ids(
-                    queued_users
-                        .iter_mut()
-                        .map(|(k, v)| (k.0, v.clone()))
-                        .collect(),
-                ) {
-                    Ok(game) => {
-                        *self = UnoGame::Ongoing {
-                            channel_id: *channel_id,
-                            host: *host,
-                            game,
-                        };
-                        Ok(())
-                    }
-                    Err(err) => Err(err),
-                }
-            }
-            UnoGame::Ongoing { .. } => Ok(()),
-        }
-    }
-}
-
 // Custom user data passed to all command functions
 pub struct Data {
-    matches: Mutex<HashMap<ChannelId, UnoGame>>,
+    matches: MatchService<ChannelId, UserId>,
 }
 
 async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
@@ -77,11 +22,11 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
     match error {
         poise::FrameworkError::Setup { error, .. } => panic!("Failed to start bot: {error:?}"),
         poise::FrameworkError::Command { error, ctx } => {
-            println!("Error in command `{}`: {:?}", ctx.command().name, error,);
+            error!("Error in command `{}`: {:?}", ctx.command().name, error,);
         }
         error => {
             if let Err(e) = poise::builtins::on_error(error).await {
-                println!("Error while handling error: {e}")
+                error!("Error while handling error: {e}")
             }
         }
     }
@@ -89,6 +34,8 @@ async fn on_error(error: poise::FrameworkError<'_, Data, Error>) {
 
 #[tokio::main]
 async fn main() {
+    tracing_subscriber::fmt::init();
+
     let options = poise::FrameworkOptions {
         commands: vec![
             autocomplete::greet(),
@@ -102,13 +49,13 @@ async fn main() {
         /// This code is run before every command
         pre_command: |ctx| {
             Box::pin(async move {
-                println!("Executing command {}...", ctx.command().qualified_name);
+                info!("Executing command {}...", ctx.command().qualified_name);
             })
         },
         /// This code is run after a command if it was successful (returned Ok)
         post_command: |ctx| {
             Box::pin(async move {
-                println!("Executed command {}!", ctx.command().qualified_name);
+                info!("Executed command {}!", ctx.command().qualified_name);
             })
         },
         /// Every command invocation must pass this check to continue execution
@@ -118,7 +65,7 @@ async fn main() {
         skip_checks_for_owners: false,
         event_handler: |ctx, event, _framework, data| {
             Box::pin(async move {
-                println!("Got an event in event handler: {:?}", event.name());
+                info!("Got an event in event handler: {:?}", event.name());
 
                 UnoButton::handle_event(ctx, event, data).await;
 
@@ -136,7 +83,7 @@ async fn main() {
         .setup(move |_ctx, _ready, _framework| {
             Box::pin(async move {
                 Ok(Data {
-                    matches: Mutex::new(HashMap::new()),
+                    matches: MatchService::new(MatchOptions::default()),
                 })
             })
         })